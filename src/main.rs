@@ -1,7 +1,7 @@
 mod trustlate;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use trustlate::config::Config;
+use std::path::{Path, PathBuf};
+use trustlate::{config::Config, errors::TrustlateError};
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
@@ -30,15 +30,48 @@ enum Commands {
         /// differences with respect to the base language translation file
         #[clap(long, short, action)]
         show_diffs: bool,
+        /// format in which to report the differences
+        #[clap(long, value_enum, default_value_t)]
+        format: trustlate::reports::CheckFormat,
+        /// set this to make the differences with the warning severity fail the check too
+        #[clap(long, action)]
+        strict: bool,
     },
     /// makes the target languages translation files conform to the base language translation file
     /// structure while filling the missing translations
     Fix {
+        /// text to fill the missing translations with, overriding the `filling` of the
+        /// configuration
         #[clap(long, short, action)]
         filling: Option<String>,
+        /// set this to remove the translations that the base language translation file does not
+        /// have from the target languages translation files
+        #[clap(long, short, action)]
+        prune: bool,
     },
     /// generates the translation client code for the specified language
     Generate,
+    /// exports the translations to the resource files of other localization tools
+    Export {
+        /// format of the exported files
+        #[clap(long, value_enum)]
+        format: trustlate::exports::ExportFormat,
+        /// directory to write the exported files to, the `target_dir` of the configuration by
+        /// default
+        #[clap(long, short, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+    /// imports the translations of the target languages from the resource files of other
+    /// localization tools into their translation files
+    Import {
+        /// format of the imported files
+        #[clap(long, value_enum)]
+        format: trustlate::imports::ImportFormat,
+        /// directory to read the imported files from, the `target_dir` of the configuration by
+        /// default
+        #[clap(long, short, value_name = "DIR")]
+        input: Option<PathBuf>,
+    },
     // {
     // /// programming language in which to generate the code
     // #[clap(value_enum, default_value_t)]
@@ -46,16 +79,27 @@ enum Commands {
     // },
 }
 
-fn main() -> Result<(), trustlate::errors::TrustlateError> {
-    let cli = Cli::parse();
+fn main() {
+    match run(Cli::parse()) {
+        Ok(true) => {}
+        // The command already reported why it did not succeed (e.g. the differences `check` found).
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{}", err.report());
+            std::process::exit(err.exit_code());
+        }
+    }
+}
+
+/// Runs the command, returning whether it succeeded.
+fn run(cli: Cli) -> Result<bool, TrustlateError> {
+    let default_config_path = Path::new(".trustlaterc.json");
     let config = if let Some(config_path) = cli.config {
         Config::from_file(&config_path)?
+    } else if default_config_path.exists() {
+        Config::from_file(default_config_path)?
     } else {
-        if let Ok(config) = Config::from_file(std::path::Path::new(".trustlaterc.json")) {
-            config
-        } else {
-            Config::default()
-        }
+        Config::default()
     };
 
     match &cli.command {
@@ -64,24 +108,54 @@ fn main() -> Result<(), trustlate::errors::TrustlateError> {
         _ => {
             let mut translations_trees = trustlate::generate_trees(&config)?;
             match &cli.command {
-                Commands::Check { show_diffs } => {
-                    trustlate::check_trees(&config, &translations_trees, *show_diffs);
+                Commands::Check {
+                    show_diffs,
+                    format,
+                    strict,
+                } => {
+                    return Ok(trustlate::check_trees(
+                        &config,
+                        &translations_trees,
+                        *show_diffs,
+                        *format,
+                        *strict,
+                    ));
                 }
-                Commands::Fix { filling } => trustlate::harmonize_files(
+                Commands::Fix { filling, prune } => trustlate::harmonize_files(
                     &config,
                     &mut translations_trees,
-                    filling.as_deref().unwrap_or("[FILLING]"),
+                    filling.as_deref().unwrap_or(config.filling()),
+                    *prune,
                 )?,
                 Commands::Generate => {
-                    let ok = trustlate::check_trees(&config, &translations_trees, true);
-                    if ok {
-                        trustlate::generate_code(&config, &translations_trees)?
+                    let ok = trustlate::check_trees(
+                        &config,
+                        &translations_trees,
+                        true,
+                        trustlate::reports::CheckFormat::Table,
+                        false,
+                    );
+                    if !ok {
+                        return Ok(false);
                     }
+                    trustlate::generate_code(&config, &translations_trees)?
                 }
+                Commands::Export { format, output } => trustlate::export_translations(
+                    &config,
+                    &translations_trees,
+                    *format,
+                    output.as_deref().unwrap_or(&config.target_dir),
+                )?,
+                Commands::Import { format, input } => trustlate::import_translations(
+                    &config,
+                    &translations_trees,
+                    *format,
+                    input.as_deref().unwrap_or(&config.target_dir),
+                )?,
                 _ => unreachable!(),
             }
         }
     }
 
-    Ok(())
+    Ok(true)
 }
@@ -0,0 +1,488 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::language_tag,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, form_segments, placeholder_kind, plural_count_variable, select_case_text,
+    select_type_name, to_camel_case, to_pascal_case, type_name, CodegenNode, FormSegment,
+};
+
+/// Types and functions the generated methods rely on to format their parameters, using the
+/// cultures of .NET.
+pub const CSHARP_HELPERS: &str = r##"public sealed record Money(decimal Amount, string Currency);
+
+internal static class TrustlateFormat
+{
+    public static string Number(double value, CultureInfo culture) =>
+        value.ToString("#,0.##########", culture);
+
+    public static string Currency(Money value, CultureInfo culture)
+    {
+        var format = (NumberFormatInfo)culture.NumberFormat.Clone();
+        format.CurrencySymbol = value.Currency;
+        return value.Amount.ToString("C", format);
+    }
+
+    public static string Date(DateTime value, CultureInfo culture) => value.ToString("d", culture);
+}"##;
+
+const CSHARP_KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Generates a C# file with an interface per level of the translations tree (`ITrustlate` for
+/// the top one), implemented by one sealed class per language, and a `TrustlateFactory` to pick
+/// the translations of a language.
+///
+/// The base language, first in `trees`, declares the interfaces and is the one
+/// `TrustlateFactory.ForLocale` returns for the cultures it does not know.
+pub fn generate_csharp(
+    trees: &[(&String, TranslationsTree)],
+    namespace: &str,
+) -> Result<String, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+
+    let mut code = format!(
+        "// <auto-generated>\n// Code generated by trustlate. DO NOT EDIT.\n// </auto-generated>\n#nullable enable\nusing System;\nusing System.Collections.Generic;\nusing System.Globalization;\n\nnamespace {};\n\n",
+        namespace
+    );
+    code += CSHARP_HELPERS;
+    code += "\n\ninternal static class TrustlateCultures\n{\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "    public static readonly CultureInfo {} = CultureInfo.GetCultureInfo({});\n",
+            to_pascal_case(lang),
+            csharp_string(&language_tag(lang))
+        );
+    }
+    code += "}\n\n";
+    generate_select_enums(&mut code, &base_nodes, &[]);
+    generate_interfaces(&mut code, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_classes(&mut code, &nodes, &[], lang);
+    }
+
+    code += "public static class TrustlateFactory\n{\n";
+    code += "    /// <summary>The languages of the translations, the first one being the base language.</summary>\n";
+    code += &format!(
+        "    public static readonly IReadOnlyList<string> Locales = new[] {{ {} }};\n\n",
+        trees
+            .iter()
+            .map(|(lang, _)| csharp_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    code += "    /// <summary>Returns the translations of a language, falling back to the base language.</summary>\n";
+    code += "    public static ITrustlate ForLocale(string lang) => lang switch\n    {\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "        {} => {}.Instance,\n",
+            csharp_string(lang),
+            class_name(&[], lang)
+        );
+    }
+    code += &format!(
+        "        _ => {}.Instance,\n    }};\n\n",
+        class_name(&[], base_lang)
+    );
+    code += "    /// <summary>\n    /// Returns the translations of a culture, falling back to the ones of its language and then to the base\n    /// language.\n    /// </summary>\n";
+    code += "    public static ITrustlate ForLocale(CultureInfo culture) =>\n        ForLocale(((IList<string>)Locales).Contains(culture.Name) ? culture.Name : culture.TwoLetterISOLanguageName);\n}\n";
+    Ok(code)
+}
+
+fn generate_select_enums(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "public enum {}\n{{\n",
+                    select_type_name(&[path, &[key]].concat(), parameter)
+                );
+                for (case, _) in cases {
+                    *code += &format!("    {},\n", to_pascal_case(case));
+                }
+                *code += "}\n\n";
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_interfaces(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("public interface {}\n{{\n", interface_name(path));
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "    {} {} {{ get; }}\n",
+                    interface_name(&[path, &[key]].concat()),
+                    to_pascal_case(key)
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                let signature = make_signature(&[path, &[key]].concat(), base);
+                match base {
+                    LeafType::LiteralLeaf(_) => *code += &format!("    {} {{ get; }}\n", signature),
+                    _ => *code += &format!("    {};\n", signature),
+                }
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_interfaces(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_classes(code: &mut String, nodes: &[CodegenNode], path: &[&str], lang: &str) {
+    let name = class_name(path, lang);
+    // Only the classes of the whole translations of a language are meant to be used directly.
+    let visibility = if path.is_empty() {
+        "public"
+    } else {
+        "internal"
+    };
+    *code += &format!(
+        "{} sealed class {} : {}\n{{\n    public static readonly {} Instance = new();\n\n    private {}() {{ }}\n",
+        visibility,
+        name,
+        interface_name(path),
+        name,
+        name
+    );
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                *code += &format!(
+                    "\n    public {} {} => {}.Instance;\n",
+                    interface_name(&child_path),
+                    to_pascal_case(key),
+                    class_name(&child_path, lang)
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "\n    public {}{}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(&leaf_path, leaf, base, lang)
+                );
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_classes(code, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+/// Declares the PascalCase member of a translation: a get-only property for literal translations,
+/// and a method taking its parameters for the rest of them.
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = to_pascal_case(path.last().unwrap());
+    let params = make_params(base.parameters());
+    let params = match base {
+        LeafType::LiteralLeaf(_) => return format!("string {}", name),
+        LeafType::ParametrizedLeaf { .. } => params,
+        LeafType::PluralLeaf { parameter, .. } => [
+            vec![format!(
+                "int {}",
+                make_identifier(&to_camel_case(parameter))
+            )],
+            params,
+        ]
+        .concat(),
+        LeafType::SelectLeaf { parameter, .. } => [
+            vec![format!(
+                "{} {}",
+                select_type_name(path, parameter),
+                make_identifier(&to_camel_case(parameter))
+            )],
+            params,
+        ]
+        .concat(),
+    };
+    format!("string {}({})", name, params.join(", "))
+}
+
+fn make_params(params: &[Parameter]) -> Vec<String> {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                "{} {}",
+                make_csharp_type(param.kind),
+                make_identifier(&to_camel_case(&param.name))
+            )
+        })
+        .collect()
+}
+
+fn make_csharp_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "string",
+        ParameterKind::Number => "double",
+        ParameterKind::Date => "DateTime",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds what follows the signature of the member of a translation of `lang`: an expression
+/// body, a `switch` expression over the enum declared from `base` (the base language translation)
+/// for selects, or a block for plurals.
+fn make_body(path: &[&str], leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!(" => {};", csharp_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => {
+            format!(" => {};", make_form_expression(raw, parameters, None, lang))
+        }
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_camel_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::from("\n    {\n");
+            if !branches.is_empty() {
+                body += &format!(
+                    "        var {} = Math.Abs({});\n",
+                    variable,
+                    make_identifier(&to_camel_case(parameter))
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "        if ({})\n        {{\n            return {};\n        }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body += &format!(
+                "        return {};\n    }}",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            );
+            body
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let type_name = select_type_name(path, parameter);
+            let selector = make_identifier(&to_camel_case(parameter));
+            let mut body = format!(" => {} switch\n    {{\n", selector);
+            for (case, _) in base_cases {
+                body += &format!(
+                    "        {}.{} => {},\n",
+                    type_name,
+                    to_pascal_case(case),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + &format!(
+                "        _ => throw new ArgumentOutOfRangeException(nameof({})),\n    }};",
+                selector
+            )
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the `$"..."` interpolated string returning a translation, its placeholders being
+/// formatted with the culture of `lang`, and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let culture = format!("TrustlateCultures.{}", to_pascal_case(lang));
+    let segments = form_segments(form);
+    if segments
+        .iter()
+        .all(|segment| matches!(segment, FormSegment::Text(_)))
+    {
+        return csharp_string(form);
+    }
+
+    let template: String = segments
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => escape_csharp(&text).replace('{', "{{").replace('}', "}}"),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_camel_case(&name));
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => format!("{{{}}}", ident),
+                    ParameterKind::Number => {
+                        format!("{{TrustlateFormat.Number({}, {})}}", ident, culture)
+                    }
+                    ParameterKind::Date => {
+                        format!("{{TrustlateFormat.Date({}, {})}}", ident, culture)
+                    }
+                    ParameterKind::Currency => {
+                        format!("{{TrustlateFormat.Currency({}, {})}}", ident, culture)
+                    }
+                }
+            }
+        })
+        .collect();
+    format!("$\"{}\"", template)
+}
+
+/// C# string literal of a text.
+fn csharp_string(text: &str) -> String {
+    format!("\"{}\"", escape_csharp(text))
+}
+
+fn escape_csharp(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Name of the interface of the level of the tree at `path`, with the `I` prefix of .NET.
+fn interface_name(path: &[&str]) -> String {
+    format!("I{}", type_name(path))
+}
+
+/// Name of the sealed class implementing the interface of the level of the tree at `path` for a
+/// language, e.g. `CartEn` for the `ICart` of English.
+fn class_name(path: &[&str], lang: &str) -> String {
+    format!("{}{}", type_name(path), to_pascal_case(lang))
+}
+
+fn make_identifier(name: &str) -> String {
+    if CSHARP_KEYWORDS.contains(&name) {
+        format!("@{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_csharp(&trees, "Trustlate").unwrap();
+        assert!(code.contains("var n1 = Math.Abs(n);"));
+        assert!(code.contains("if (n1 == 1)"));
+    }
+}
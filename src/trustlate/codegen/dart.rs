@@ -0,0 +1,481 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::{base_language, locale_formats},
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, escape_keyword, form_segments, placeholder_kind, plural_count_variable,
+    select_case_text, select_type_name, to_camel_case, to_pascal_case, type_name, CodegenNode,
+    FormSegment,
+};
+
+/// The `Money` class of the currency placeholders and the private `_format*` functions the classes
+/// of every language call, so that the library does not depend on `intl`.
+pub const DART_HELPERS: &str = r#"class Money {
+  final double amount;
+  final String currency;
+
+  const Money(this.amount, this.currency);
+}
+
+String _formatNumber(num value, String decimalSeparator, String groupSeparator) {
+  final text = value == value.truncate() ? value.toInt().toString() : value.toString();
+  return _groupDigits(text, decimalSeparator, groupSeparator);
+}
+
+String _formatCurrency(Money value, String decimalSeparator, String groupSeparator, bool currencyFirst) {
+  final amount = _groupDigits(value.amount.toStringAsFixed(2), decimalSeparator, groupSeparator);
+  return currencyFirst ? '${value.currency} $amount' : '$amount ${value.currency}';
+}
+
+String _formatDate(DateTime value, String pattern) => pattern
+    .replaceAll('yyyy', value.year.toString().padLeft(4, '0'))
+    .replaceAll('MM', value.month.toString().padLeft(2, '0'))
+    .replaceAll('dd', value.day.toString().padLeft(2, '0'));
+
+String _groupDigits(String number, String decimalSeparator, String groupSeparator) {
+  final negative = number.startsWith('-');
+  final parts = (negative ? number.substring(1) : number).split('.');
+  final integer = parts[0];
+  final buffer = StringBuffer(negative ? '-' : '');
+  for (var i = 0; i < integer.length; i++) {
+    if (i > 0 && (integer.length - i) % 3 == 0) {
+      buffer.write(groupSeparator);
+    }
+    buffer.write(integer[i]);
+  }
+  if (parts.length > 1) {
+    buffer
+      ..write(decimalSeparator)
+      ..write(parts[1]);
+  }
+  return buffer.toString();
+}"#;
+
+const DART_KEYWORDS: &[&str] = &[
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+    "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+    "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+    "with",
+];
+
+/// Generates a Dart library with an abstract class per level of the translations tree,
+/// implemented by one class per language, and the functions a Flutter `LocalizationsDelegate`
+/// needs to resolve the translations of a locale.
+///
+/// The base language, first in `trees`, declares the abstract classes and is the one
+/// `resolveTrustlate` resolves the unsupported locales to.
+pub fn generate_dart(trees: &[(&String, TranslationsTree)]) -> Result<String, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+
+    let mut code = String::from("// Code generated by trustlate. DO NOT EDIT.\n// ignore_for_file: type=lint, unused_element\n\n");
+    code += DART_HELPERS;
+    code += "\n\n";
+    generate_select_enums(&mut code, &base_nodes, &[]);
+    generate_abstract_classes(&mut code, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_classes(&mut code, &nodes, &[], lang);
+    }
+
+    code += "/// The languages of the translations, the first one being the base language.\n";
+    code += &format!(
+        "const List<String> trustlateLanguages = [{}];\n\n",
+        trees
+            .iter()
+            .map(|(lang, _)| dart_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    code += "/// Returns the translations of a language, falling back to the base language.\n";
+    code += "Trustlate getTrustlate(String lang) => switch (lang) {\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "      {} => const {}(),\n",
+            dart_string(lang),
+            class_name(&[], lang)
+        );
+    }
+    code += &format!(
+        "      _ => const {}(),\n    }};\n\n",
+        class_name(&[], base_lang)
+    );
+
+    code += "/// Returns the language of the translations that fits a locale best, if any: the one of the\n/// language and country, or the one of the language.\n";
+    code += "String? _resolveLanguage(String languageCode, [String? countryCode]) => switch ((languageCode, countryCode)) {\n";
+    for (pattern, lang) in resolution_patterns(trees) {
+        code += &format!("      {} => {},\n", pattern, dart_string(lang));
+    }
+    code += "      _ => null,\n    };\n\n";
+    code += "/// Whether there are translations for a locale, as `LocalizationsDelegate.isSupported` expects.\n";
+    code += "bool isTrustlateSupported(String languageCode, [String? countryCode]) =>\n    _resolveLanguage(languageCode, countryCode) != null;\n\n";
+    code += "/// Returns the translations of a locale, to be loaded by a `LocalizationsDelegate`, falling back to\n/// the base language.\n";
+    code += &format!(
+        "Trustlate resolveTrustlate(String languageCode, [String? countryCode]) =>\n    getTrustlate(_resolveLanguage(languageCode, countryCode) ?? {});\n",
+        dart_string(base_lang)
+    );
+    Ok(code)
+}
+
+/// Pairs the `(languageCode, countryCode)` patterns of a switch with the language they resolve
+/// to: first the ones of a language and a country, then the ones of a language alone, and last
+/// the ones matching a language of the translations whatever the country is.
+fn resolution_patterns<'a>(trees: &'a [(&String, TranslationsTree)]) -> Vec<(String, &'a str)> {
+    let mut with_country = vec![];
+    let mut without_country = vec![];
+    let mut any_country = vec![];
+    for (lang, _) in trees {
+        let mut subtags = lang.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default();
+        let country = subtags.next();
+        for code in [language.to_string(), base_language(language)] {
+            let language = dart_string(&code);
+            match country {
+                Some(country) => {
+                    with_country.push((
+                        format!("({}, {})", language, dart_string(&country.to_uppercase())),
+                        lang.as_str(),
+                    ));
+                    any_country.push((format!("({}, _)", language), lang.as_str()));
+                }
+                None => without_country.push((format!("({}, _)", language), lang.as_str())),
+            }
+        }
+    }
+
+    let mut patterns: Vec<(String, &str)> = vec![];
+    for (pattern, lang) in [with_country, without_country, any_country].concat() {
+        if !patterns.iter().any(|(p, _)| *p == pattern) {
+            patterns.push((pattern, lang));
+        }
+    }
+    patterns
+}
+
+fn generate_select_enums(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "enum {} {{ {} }}\n\n",
+                    dart_select_type_name(&[path, &[key]].concat(), parameter),
+                    cases
+                        .iter()
+                        .map(|(case, _)| make_identifier(&to_camel_case(case)))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_abstract_classes(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("abstract class {} {{\n", dart_type_name(path));
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "  {} get {};\n",
+                    dart_type_name(&[path, &[key]].concat()),
+                    make_identifier(&to_camel_case(key))
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                *code += &format!("  {};\n", make_signature(&[path, &[key]].concat(), base));
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_abstract_classes(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_classes(code: &mut String, nodes: &[CodegenNode], path: &[&str], lang: &str) {
+    let name = class_name(path, lang);
+    *code += &format!(
+        "class {} implements {} {{\n  const {}();\n",
+        name,
+        dart_type_name(path),
+        name
+    );
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                *code += &format!(
+                    "\n  @override\n  {} get {} => const {}();\n",
+                    dart_type_name(&child_path),
+                    make_identifier(&to_camel_case(key)),
+                    class_name(&child_path, lang)
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "\n  @override\n  {}{}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(&leaf_path, leaf, base, lang)
+                );
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_classes(code, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+/// Declares the member of a translation: a getter for literal translations, and a method taking
+/// its placeholders as required named parameters for the rest of them.
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = make_identifier(&to_camel_case(path.last().unwrap()));
+    let params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) => format!("String get {}", name),
+        LeafType::ParametrizedLeaf { .. } => format!("String {}({})", name, params),
+        LeafType::PluralLeaf { parameter, .. } => format!(
+            "String {}(int {}{})",
+            name,
+            make_identifier(&to_camel_case(parameter)),
+            prefix_comma(params)
+        ),
+        LeafType::SelectLeaf { parameter, .. } => format!(
+            "String {}({} {}{})",
+            name,
+            dart_select_type_name(path, parameter),
+            make_identifier(&to_camel_case(parameter)),
+            prefix_comma(params)
+        ),
+    }
+}
+
+fn prefix_comma(params: String) -> String {
+    if params.is_empty() {
+        params
+    } else {
+        format!(", {}", params)
+    }
+}
+
+fn make_params(params: &[Parameter]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| {
+            format!(
+                "required {} {}",
+                make_dart_type(param.kind),
+                make_identifier(&to_camel_case(&param.name))
+            )
+        })
+        .collect();
+    format!("{{{}}}", params.join(", "))
+}
+
+fn make_dart_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "String",
+        ParameterKind::Number => "num",
+        ParameterKind::Date => "DateTime",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds what follows the signature of the member of a translation of `lang`: an arrow body, a
+/// `switch` expression over the enum declared from `base` (the base language translation) for
+/// selects, or a block for plurals.
+fn make_body(path: &[&str], leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!(" => {};", dart_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => {
+            format!(" => {};", make_form_expression(raw, parameters, None, lang))
+        }
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_camel_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::from(" {\n");
+            if !branches.is_empty() {
+                body += &format!(
+                    "    final {} = {}.abs();\n",
+                    variable,
+                    make_identifier(&to_camel_case(parameter))
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "    if ({}) {{\n      return {};\n    }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body += &format!(
+                "    return {};\n  }}",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            );
+            body
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let type_name = dart_select_type_name(path, parameter);
+            let mut body = format!(
+                " => switch ({}) {{\n",
+                make_identifier(&to_camel_case(parameter))
+            );
+            for (case, _) in base_cases {
+                body += &format!(
+                    "        {}.{} => {},\n",
+                    type_name,
+                    make_identifier(&to_camel_case(case)),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + "      };"
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the single quoted string returning a translation, interpolating its placeholders
+/// formatted with the separators and date pattern of `lang`, and the `count` of a plural as a
+/// number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let formats = locale_formats(lang);
+    let (decimal, group) = (
+        dart_string(formats.decimal_separator),
+        dart_string(formats.group_separator),
+    );
+    let template: String = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => escape_dart(&text),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_camel_case(&name));
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => format!("${{{}}}", ident),
+                    ParameterKind::Number => {
+                        format!("${{_formatNumber({}, {}, {})}}", ident, decimal, group)
+                    }
+                    ParameterKind::Date => format!(
+                        "${{_formatDate({}, {})}}",
+                        ident,
+                        dart_string(formats.date_pattern)
+                    ),
+                    ParameterKind::Currency => format!(
+                        "${{_formatCurrency({}, {}, {}, {})}}",
+                        ident, decimal, group, formats.currency_first
+                    ),
+                }
+            }
+        })
+        .collect();
+    format!("'{}'", template)
+}
+
+/// Dart string literal of a text.
+fn dart_string(text: &str) -> String {
+    format!("'{}'", escape_dart(text))
+}
+
+fn escape_dart(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "\\'")
+        .replace('$', "\\$")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Name of the class implementing the abstract class of the level of the tree at `path` for a
+/// language.
+fn class_name(path: &[&str], lang: &str) -> String {
+    format!("{}{}", dart_type_name(path), to_pascal_case(lang))
+}
+
+/// Name of the abstract class of the level of the tree at `path`, see [`type_name`].
+fn dart_type_name(path: &[&str]) -> String {
+    public_name(&type_name(path), "K")
+}
+
+/// Name of the enum of the selector of the select translation at `path`, see
+/// [`select_type_name`].
+fn dart_select_type_name(path: &[&str], selector: &str) -> String {
+    public_name(&select_type_name(path, selector), "K")
+}
+
+fn make_identifier(name: &str) -> String {
+    escape_keyword(public_name(name, "k"), DART_KEYWORDS)
+}
+
+/// Names starting with an underscore are private to their library in Dart, so the ones that were
+/// prefixed with it for starting with a digit get `prefix` instead (e.g. `k404` for `404`).
+fn public_name(name: &str, prefix: &str) -> String {
+    match name.strip_prefix('_') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => {
+            format!("{}{}", prefix, rest)
+        }
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_dart(&trees).unwrap();
+        assert!(code.contains("final n1 = n.abs();"));
+        assert!(code.contains("if (n1 == 1) {"));
+    }
+}
@@ -1,27 +1,123 @@
 use regex::Regex;
 
-use crate::trustlate::translations_tree::TranslationTreeNode;
+use super::plural_count_variable;
+use crate::trustlate::locales::locale_formats;
+use crate::trustlate::plurals::{plural_branches, PluralCategory};
+use crate::trustlate::translations_tree::{
+    find_parameter, placeholders, Parameter, ParameterKind, TranslationTreeNode,
+};
 use crate::trustlate::{errors::TrustlateError, translations_tree::LeafType, TranslationsTree};
 
+/// The `Money` struct of the currency placeholders and the `format*` functions the methods of every
+/// `Trustlate<LANG>` call, written once at the top of `trustlate.go`.
+pub const GOLANG_HELPERS: &str = r#"type Money struct {
+    Amount   float64
+    Currency string
+}
+
+func formatNumber(value float64, decimalSeparator string, groupSeparator string) string {
+    return groupDigits(strconv.FormatFloat(value, 'f', -1, 64), decimalSeparator, groupSeparator)
+}
+
+func formatCurrency(value Money, decimalSeparator string, groupSeparator string, currencyFirst bool) string {
+    amount := groupDigits(strconv.FormatFloat(value.Amount, 'f', 2, 64), decimalSeparator, groupSeparator)
+    if currencyFirst {
+        return value.Currency + " " + amount
+    }
+    return amount + " " + value.Currency
+}
+
+func formatDate(value time.Time, layout string) string {
+    return value.Format(layout)
+}
+
+func groupDigits(number string, decimalSeparator string, groupSeparator string) string {
+    sign := ""
+    if strings.HasPrefix(number, "-") {
+        sign = "-"
+        number = number[1:]
+    }
+    integer, fraction, hasFraction := strings.Cut(number, ".")
+    var grouped strings.Builder
+    for i, digit := range integer {
+        if i > 0 && (len(integer)-i)%3 == 0 {
+            grouped.WriteString(groupSeparator)
+        }
+        grouped.WriteRune(digit)
+    }
+    if hasFraction {
+        return sign + grouped.String() + decimalSeparator + fraction
+    }
+    return sign + grouped.String()
+}"#;
+
 #[derive(Debug)]
 pub struct GolangGenerationFunc {
     lang: String,
-    params: Option<Vec<String>>,
+    params: Option<Vec<Parameter>>,
     path: Vec<String>,
     translation: String,
+    /// The count parameter and the forms of a plural translation.
+    plural: Option<(String, Vec<(PluralCategory, String)>)>,
+    /// The selector parameter and the cases of a select translation.
+    select: Option<(String, Vec<(String, String)>)>,
 }
 
 impl GolangGenerationFunc {
     pub fn function_form(&self) -> String {
         let receiver_name = make_receiver_name(&self.lang);
         let fn_name = make_function_name(&self.path);
+        let params = self.params.as_deref().unwrap_or_default();
+
+        if let Some((count, forms)) = &self.plural {
+            let fn_params = make_plural_function_params(count, params);
+            let n = plural_count_variable(count, params, str::to_string);
+            let (branches, other) = plural_branches(&self.lang, forms, &n);
+            let mut body =
+                format!("   {n} := {count}\n   if {n} < 0 {{\n      {n} = -{n}\n   }}\n");
+            for (condition, form) in branches {
+                body += &format!(
+                    "   if {condition} {{\n      return {}\n   }}\n",
+                    make_form_expression(form, params, Some(count), &self.lang)
+                );
+            }
+            body += &format!(
+                "   return {}\n",
+                make_form_expression(other, params, Some(count), &self.lang)
+            );
+            return format!(
+                "func (trl *{receiver_name}) {fn_name}({fn_params}) string {{\n{body}}}"
+            );
+        }
+
+        if let Some((selector, cases)) = &self.select {
+            let fn_params = make_select_function_params(selector, &self.select_type_name(), params);
+            let mut body = format!("   switch {selector} {{\n");
+            let mut other = "";
+            for (case, text) in cases {
+                if case == "other" {
+                    other = text;
+                } else {
+                    body += &format!(
+                        "   case \"{case}\":\n      return {}\n",
+                        make_form_expression(text, params, None, &self.lang)
+                    );
+                }
+            }
+            body += &format!(
+                "   default:\n      return {}\n   }}\n",
+                make_form_expression(other, params, None, &self.lang)
+            );
+            return format!(
+                "func (trl *{receiver_name}) {fn_name}({fn_params}) string {{\n{body}}}"
+            );
+        }
 
         match &self.params {
-            Some(params) => {
+            Some(_) => {
                 let fn_params = make_function_params(params);
-                let sprintf_params = make_sprintf_params(params);
-                let translation = make_sprintf_translation(&self.translation);
-                format!("func (trl *{receiver_name}) {fn_name}({fn_params}) string {{\n   return fmt.Sprintf(\"{translation}\", {sprintf_params})\n}}")
+                let translation = make_form_expression(&self.translation, params, None, &self.lang);
+                format!("func (trl *{receiver_name}) {fn_name}({fn_params}) string {{\n   return {translation}\n}}")
             }
             None => {
                 let translation = &self.translation;
@@ -35,6 +131,21 @@ impl GolangGenerationFunc {
     pub fn interface_entry_form(&self) -> String {
         let fn_name = make_function_name(&self.path);
 
+        if let Some((count, _)) = &self.plural {
+            let fn_params =
+                make_plural_function_params(count, self.params.as_deref().unwrap_or_default());
+            return format!("{fn_name}({fn_params}) string");
+        }
+
+        if let Some((selector, _)) = &self.select {
+            let fn_params = make_select_function_params(
+                selector,
+                &self.select_type_name(),
+                self.params.as_deref().unwrap_or_default(),
+            );
+            return format!("{fn_name}({fn_params}) string");
+        }
+
         match &self.params {
             Some(params) => {
                 let fn_params = make_function_params(params);
@@ -45,11 +156,40 @@ impl GolangGenerationFunc {
             }
         }
     }
+
+    /// Declares the type and constants of the selector of a select translation, if it is one.
+    pub fn type_declaration_form(&self) -> Option<String> {
+        let (_, cases) = self.select.as_ref()?;
+        let type_name = self.select_type_name();
+        let constants: String = cases
+            .iter()
+            .map(|(case, _)| {
+                format!(
+                    "    {type_name}{} {type_name} = \"{case}\"\n",
+                    capitalize_first_letter(case)
+                )
+            })
+            .collect();
+        Some(format!("type {type_name} string\n\nconst (\n{constants})"))
+    }
+
+    fn select_type_name(&self) -> String {
+        let selector = self
+            .select
+            .as_ref()
+            .map(|(selector, _)| selector.as_str())
+            .unwrap_or_default();
+        format!(
+            "{}_{}",
+            make_function_name(&self.path),
+            capitalize_first_letter(selector)
+        )
+    }
 }
 
 pub fn generate_golang(
     tree: TranslationsTree,
-    lang: &String,
+    lang: &str,
 ) -> Result<Vec<GolangGenerationFunc>, TrustlateError> {
     let mut gen: Vec<GolangGenerationFunc> = Vec::new();
     let node = TranslationTreeNode::NonLeaf(tree.children);
@@ -71,6 +211,8 @@ fn collect_fns_rec(
                     path,
                     translation,
                     lang: lang.to_string(),
+                    plural: None,
+                    select: None,
                 });
             }
             LeafType::ParametrizedLeaf { parameters, raw } => {
@@ -79,6 +221,38 @@ fn collect_fns_rec(
                     path,
                     translation: raw,
                     lang: lang.to_string(),
+                    plural: None,
+                    select: None,
+                });
+            }
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                raw,
+            } => {
+                acc.push(GolangGenerationFunc {
+                    params: Some(parameters),
+                    path,
+                    translation: raw,
+                    lang: lang.to_string(),
+                    plural: Some((parameter, forms)),
+                    select: None,
+                });
+            }
+            LeafType::SelectLeaf {
+                parameter,
+                parameters,
+                cases,
+                raw,
+            } => {
+                acc.push(GolangGenerationFunc {
+                    params: Some(parameters),
+                    path,
+                    translation: raw,
+                    lang: lang.to_string(),
+                    plural: None,
+                    select: Some((parameter, cases)),
                 });
             }
         },
@@ -92,7 +266,7 @@ fn collect_fns_rec(
     }
 }
 
-fn make_function_name(path: &Vec<String>) -> String {
+fn make_function_name(path: &[String]) -> String {
     let func_name: String = capitalize_first_letter(path.first().unwrap())
         + &path
             .iter()
@@ -102,41 +276,97 @@ fn make_function_name(path: &Vec<String>) -> String {
     func_name
 }
 
-fn make_receiver_name(lang: &String) -> String {
+fn make_receiver_name(lang: &str) -> String {
     format!("Trustlate{}", lang.to_uppercase())
 }
 
-fn make_sprintf_params(params: &Vec<String>) -> String {
-    let last_param = params.last().unwrap();
-    let mut aux: String = params[..params.len() - 1]
+fn make_function_params(params: &[Parameter]) -> String {
+    params
         .iter()
-        .map(|param| format!("{param}, "))
-        .collect();
-    aux += format!("{last_param}").as_str();
-    aux
+        .map(|param| format!("{} {}", param.name, make_golang_type(param.kind)))
+        .collect::<Vec<String>>()
+        .join(", ")
 }
 
-fn make_sprintf_translation(translation: &str) -> String {
-    let re = Regex::new(r"\{\{.*?\}\}").unwrap();
-    let result = re.replace_all(translation, "%s");
-    result.into()
+fn make_plural_function_params(count: &str, params: &[Parameter]) -> String {
+    params.iter().fold(format!("{count} int"), |acc, param| {
+        format!("{acc}, {} {}", param.name, make_golang_type(param.kind))
+    })
 }
 
-fn make_function_params(params: &Vec<String>) -> String {
-    let last_param = params.last().unwrap();
-    let mut aux: String = params[..params.len() - 1]
+fn make_select_function_params(
+    selector: &str,
+    selector_type: &str,
+    params: &[Parameter],
+) -> String {
+    params
         .iter()
-        .map(|param| format!("{param} string, "))
-        .collect();
-    aux += format!("{last_param} string").as_str();
-    aux
+        .fold(format!("{selector} {selector_type}"), |acc, param| {
+            format!("{acc}, {} {}", param.name, make_golang_type(param.kind))
+        })
+}
+
+fn make_golang_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "string",
+        ParameterKind::Number => "float64",
+        ParameterKind::Date => "time.Time",
+        ParameterKind::Currency => "Money",
+    }
 }
 
-fn is_leaf(node: &TranslationTreeNode) -> bool {
-    match node {
-        TranslationTreeNode::Leaf(_) => true,
-        _ => false,
+/// Builds the `fmt.Sprintf` call returning a translation, its placeholders being formatted with
+/// the separators and date layout of `lang`, and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let names = placeholders(form);
+    if names.is_empty() {
+        return format!("\"{form}\"");
     }
+
+    let formats = locale_formats(lang);
+    let (decimal, group) = (formats.decimal_separator, formats.group_separator);
+    let args: Vec<String> = names
+        .iter()
+        .map(|name| {
+            if Some(name.as_str()) == count {
+                return format!("formatNumber(float64({name}), \"{decimal}\", \"{group}\")");
+            }
+            let kind = find_parameter(params, name)
+                .map(|param| param.kind)
+                .unwrap_or(ParameterKind::String);
+            match kind {
+                ParameterKind::String => name.to_string(),
+                ParameterKind::Number => {
+                    format!("formatNumber({name}, \"{decimal}\", \"{group}\")")
+                }
+                ParameterKind::Date => format!(
+                    "formatDate({name}, \"{}\")",
+                    make_golang_date_layout(formats.date_pattern)
+                ),
+                ParameterKind::Currency => format!(
+                    "formatCurrency({name}, \"{decimal}\", \"{group}\", {})",
+                    formats.currency_first
+                ),
+            }
+        })
+        .collect();
+
+    let re = Regex::new(r"\{\{(.*?)\}\}").unwrap();
+    let form = form.replace('%', "%%");
+    let translation = re.replace_all(&form, "%s");
+    format!("fmt.Sprintf(\"{translation}\", {})", args.join(", "))
+}
+
+fn make_golang_date_layout(pattern: &str) -> String {
+    pattern
+        .replace("yyyy", "2006")
+        .replace("MM", "01")
+        .replace("dd", "02")
 }
 
 fn capitalize_first_letter(s: &str) -> String {
@@ -147,10 +377,19 @@ fn capitalize_first_letter(s: &str) -> String {
     }
 }
 
-fn build_type_name(path: &[&str]) -> String {
-    let mut type_str = path.iter().skip(1).fold(path[0].to_string(), |prev, curr| {
-        format!("{}_{}", prev, curr)
-    });
-    type_str.push_str("_T");
-    type_str
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let code: String = generate_golang(tree(PLURAL_N), "en")
+            .unwrap()
+            .iter()
+            .map(|gen| gen.function_form())
+            .collect();
+        assert!(code.contains("n1 := n\n"));
+        assert!(code.contains("if n1 == 1 {"));
+    }
 }
@@ -0,0 +1,486 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::language_tag,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, escape_keyword, form_segments, placeholder_kind, plural_count_variable,
+    select_case_text, to_camel_case, to_pascal_case, to_snake_case, CodegenNode, FormSegment,
+    JAVA_KEYWORDS,
+};
+
+/// Functions the generated classes rely on to format their parameters, using the locale aware
+/// formatters of the JDK.
+pub const JAVA_HELPERS: &str = r#"final class TrustlateFormat {
+    private TrustlateFormat() {
+    }
+
+    static String number(double value, Locale locale) {
+        NumberFormat format = NumberFormat.getNumberInstance(locale);
+        format.setMaximumFractionDigits(10);
+        return format.format(value);
+    }
+
+    static String currency(Trustlate.Money value, Locale locale) {
+        NumberFormat format = NumberFormat.getCurrencyInstance(locale);
+        format.setCurrency(Currency.getInstance(value.getCurrency()));
+        return format.format(value.getAmount());
+    }
+
+    static String date(LocalDate value, Locale locale) {
+        return DateTimeFormatter.ofLocalizedDate(FormatStyle.SHORT).withLocale(locale).format(value);
+    }
+}"#;
+
+/// The `Money` class of the currency placeholders, nested in the `Trustlate` interface.
+const JAVA_MONEY: &str = r#"    /** An amount of money in a currency, given by its ISO 4217 code. */
+    final class Money {
+        private final BigDecimal amount;
+        private final String currency;
+
+        public Money(BigDecimal amount, String currency) {
+            this.amount = amount;
+            this.currency = currency;
+        }
+
+        public BigDecimal getAmount() {
+            return amount;
+        }
+
+        public String getCurrency() {
+            return currency;
+        }
+    }"#;
+
+/// A generated Java file, as its path without the extension and its code.
+pub type JavaFile = (String, String);
+
+/// Generates the Java sources of a package made of:
+/// - `Trustlate.java`, with an interface nesting an interface per group of translations.
+/// - A class per language implementing it, with a nested class per group.
+/// - `TrustlateFactory.java`, with the `forLocale` entry point.
+/// - `TrustlateFormat.java`, with the functions formatting the parameters.
+///
+/// The files are laid out in the directories of the package, as build tools expect them to be.
+/// The base language goes first in `trees`, as it declares the interfaces and `forLocale` falls
+/// back to it.
+pub fn generate_java(
+    trees: &[(&String, TranslationsTree)],
+    package: &str,
+) -> Result<Vec<JavaFile>, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+    let dir = package.replace('.', "/");
+    let header = format!(
+        "// Code generated by trustlate. DO NOT EDIT.\npackage {};\n\n",
+        package
+    );
+    let mut files = vec![];
+
+    let mut interface =
+        header.clone() + "import java.math.BigDecimal;\nimport java.time.LocalDate;\n\n";
+    interface += "public interface Trustlate {\n";
+    generate_interface_members(&mut interface, &base_nodes, &["Trustlate"], "    ");
+    interface += "\n";
+    interface += JAVA_MONEY;
+    interface += "\n}\n";
+    files.push((format!("{}/Trustlate", dir), interface));
+
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        let name = class_name(lang);
+        let mut class =
+            header.clone() + "import java.time.LocalDate;\nimport java.util.Locale;\n\n";
+        class += &format!(
+            "public final class {} implements Trustlate {{\n    public static final {} INSTANCE = new {}();\n\n    private static final Locale LOCALE = Locale.forLanguageTag({});\n\n    private {}() {{\n    }}\n",
+            name,
+            name,
+            name,
+            java_string(&language_tag(lang)),
+            name
+        );
+        generate_class_members(&mut class, &nodes, &["Trustlate"], lang, "    ");
+        class += "}\n";
+        files.push((format!("{}/{}", dir, name), class));
+    }
+
+    let mut factory = header.clone()
+        + "import java.util.Arrays;\nimport java.util.Collections;\nimport java.util.List;\nimport java.util.Locale;\n\n";
+    factory += "public final class TrustlateFactory {\n";
+    factory +=
+        "    /** The languages of the translations, the first one being the base language. */\n";
+    factory += &format!(
+        "    public static final List<String> LOCALES = Collections.unmodifiableList(Arrays.asList({}));\n\n",
+        trees
+            .iter()
+            .map(|(lang, _)| java_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    factory += "    private TrustlateFactory() {\n    }\n\n";
+    factory +=
+        "    /** Returns the translations of a language, falling back to the base language. */\n";
+    factory += "    public static Trustlate forLocale(String lang) {\n";
+    factory += &format!(
+        "        if (lang == null) {{\n            return {}.INSTANCE;\n        }}\n        switch (lang) {{\n",
+        class_name(base_lang)
+    );
+    for (lang, _) in trees {
+        factory += &format!(
+            "            case {}:\n                return {}.INSTANCE;\n",
+            java_string(lang),
+            class_name(lang)
+        );
+    }
+    factory += &format!(
+        "            default:\n                return {}.INSTANCE;\n        }}\n    }}\n\n",
+        class_name(base_lang)
+    );
+    factory += "    /**\n     * Returns the translations of a locale, falling back to the ones of its language and then to the base\n     * language.\n     */\n";
+    factory += "    public static Trustlate forLocale(Locale locale) {\n        String tag = locale.toLanguageTag();\n        return forLocale(LOCALES.contains(tag) ? tag : locale.getLanguage());\n    }\n}\n";
+    files.push((format!("{}/TrustlateFactory", dir), factory));
+
+    let format = header
+        + "import java.text.NumberFormat;\nimport java.time.LocalDate;\nimport java.time.format.DateTimeFormatter;\nimport java.time.format.FormatStyle;\nimport java.util.Currency;\nimport java.util.Locale;\n\n"
+        + JAVA_HELPERS
+        + "\n";
+    files.push((format!("{}/TrustlateFormat", dir), format));
+
+    Ok(files)
+}
+
+/// Declares the methods of a level of the tree, the interfaces of its groups and the enums of
+/// its selects, `path` being the qualified name of the interface of the level.
+fn generate_interface_members(
+    code: &mut String,
+    nodes: &[CodegenNode],
+    path: &[&str],
+    indent: &str,
+) {
+    let mut first = true;
+    for node in nodes {
+        if !first {
+            *code += "\n";
+        }
+        first = false;
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "{indent}{} {}();\n",
+                    interface_name(path, key),
+                    make_identifier(&to_camel_case(key))
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                *code += &format!("{indent}{};\n", make_signature(path, key, base));
+            }
+        }
+    }
+
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                let interface = to_pascal_case(key);
+                *code += &format!("\n{indent}interface {} {{\n", interface);
+                generate_interface_members(
+                    code,
+                    children,
+                    &[path, &[interface.as_str()]].concat(),
+                    &format!("{indent}    "),
+                );
+                *code += &format!("{indent}}}\n");
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "\n{indent}enum {} {{\n{indent}    {}\n{indent}}}\n",
+                    select_enum_name(key, parameter),
+                    cases
+                        .iter()
+                        .map(|(case, _)| enum_constant(case))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+/// Implements the methods of a level of the tree, nesting a class per group, `path` being the
+/// qualified name of the interface of the level.
+fn generate_class_members(
+    code: &mut String,
+    nodes: &[CodegenNode],
+    path: &[&str],
+    lang: &str,
+    indent: &str,
+) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let method = make_identifier(&to_camel_case(key));
+                *code += &format!(
+                    "\n{indent}private final {} {} = new {}();\n\n{indent}@Override\n{indent}public {} {}() {{\n{indent}    return {};\n{indent}}}\n",
+                    interface_name(path, key),
+                    method,
+                    to_pascal_case(key),
+                    interface_name(path, key),
+                    method,
+                    method
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                *code += &format!(
+                    "\n{indent}@Override\n{indent}public {} {{\n{}{indent}}}\n",
+                    make_signature(path, key, base),
+                    make_body(leaf, base, lang)
+                        .lines()
+                        .map(|line| format!("{indent}{line}\n"))
+                        .collect::<String>()
+                );
+            }
+        }
+    }
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            let interface = to_pascal_case(key);
+            *code += &format!(
+                "\n{indent}private static final class {} implements {} {{\n",
+                interface,
+                interface_name(path, key)
+            );
+            let mut members = String::new();
+            generate_class_members(
+                &mut members,
+                children,
+                &[path, &[interface.as_str()]].concat(),
+                lang,
+                &format!("{indent}    "),
+            );
+            *code += members.trim_start_matches('\n');
+            *code += &format!("{indent}}}\n");
+        }
+    }
+}
+
+/// Declares the method of a translation at the level of the tree whose interface is `path`.
+fn make_signature(path: &[&str], key: &str, base: &LeafType) -> String {
+    let name = make_identifier(&to_camel_case(key));
+    let mut params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) | LeafType::ParametrizedLeaf { .. } => {}
+        LeafType::PluralLeaf { parameter, .. } => params.insert(
+            0,
+            format!("int {}", make_identifier(&to_camel_case(parameter))),
+        ),
+        LeafType::SelectLeaf { parameter, .. } => params.insert(
+            0,
+            format!(
+                "{}.{} {}",
+                path.join("."),
+                select_enum_name(key, parameter),
+                make_identifier(&to_camel_case(parameter))
+            ),
+        ),
+    }
+    format!("String {}({})", name, params.join(", "))
+}
+
+fn make_params(params: &[Parameter]) -> Vec<String> {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                "{} {}",
+                make_java_type(param.kind),
+                make_identifier(&to_camel_case(&param.name))
+            )
+        })
+        .collect()
+}
+
+fn make_java_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "String",
+        ParameterKind::Number => "double",
+        ParameterKind::Date => "LocalDate",
+        ParameterKind::Currency => "Trustlate.Money",
+    }
+}
+
+/// Builds the statements of the method of a translation of `lang`: an `if` per plural category
+/// of the language, or a `switch` with a case per constant of the enum declared from `base`, the
+/// base language translation, for selects.
+fn make_body(leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!("    return {};\n", java_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => format!(
+            "    return {};\n",
+            make_form_expression(raw, parameters, None)
+        ),
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_camel_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::new();
+            if !branches.is_empty() {
+                body += &format!(
+                    "    int {} = Math.abs({});\n",
+                    variable,
+                    make_identifier(&to_camel_case(parameter))
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "    if ({}) {{\n        return {};\n    }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter))
+                );
+            }
+            body + &format!(
+                "    return {};\n",
+                make_form_expression(other, parameters, Some(parameter))
+            )
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let selector = make_identifier(&to_camel_case(parameter));
+            let mut body = format!("    switch ({}) {{\n", selector);
+            for (case, _) in base_cases {
+                body += &format!(
+                    "        case {}:\n            return {};\n",
+                    enum_constant(case),
+                    make_form_expression(select_case_text(cases, case), parameters, None)
+                );
+            }
+            body + &format!(
+                "        default:\n            throw new IllegalArgumentException(String.valueOf({}));\n    }}\n",
+                selector
+            )
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the concatenation returning a translation, its placeholders being formatted with the
+/// `LOCALE` of the class by `TrustlateFormat`, and the `count` of a plural as a number.
+fn make_form_expression(form: &str, params: &[Parameter], count: Option<&str>) -> String {
+    let parts: Vec<String> = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => java_string(&text),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_camel_case(&name));
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => format!("String.valueOf({})", ident),
+                    ParameterKind::Number => format!("TrustlateFormat.number({}, LOCALE)", ident),
+                    ParameterKind::Date => format!("TrustlateFormat.date({}, LOCALE)", ident),
+                    ParameterKind::Currency => {
+                        format!("TrustlateFormat.currency({}, LOCALE)", ident)
+                    }
+                }
+            }
+        })
+        .collect();
+
+    if parts.is_empty() {
+        "\"\"".to_string()
+    } else {
+        parts.join(" + ")
+    }
+}
+
+/// Java string literal of a text.
+fn java_string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => literal += "\\\\",
+            '"' => literal += "\\\"",
+            '\n' => literal += "\\n",
+            '\r' => literal += "\\r",
+            '\t' => literal += "\\t",
+            // Unicode escapes are translated before parsing, so control characters go as octal.
+            c if c.is_control() => literal += &format!("\\{:03o}", c as u32),
+            // The rest of non ASCII characters are escaped so that the sources compile whatever
+            // the encoding the compiler reads them with is.
+            c if !c.is_ascii() => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    literal += &format!("\\u{:04x}", unit);
+                }
+            }
+            c => literal.push(c),
+        }
+    }
+    literal + "\""
+}
+
+/// Qualified name of the interface of a group, nested in the interface `path` of its level.
+fn interface_name(path: &[&str], key: &str) -> String {
+    format!("{}.{}", path.join("."), to_pascal_case(key))
+}
+
+/// Name of the enum of the cases of a select translation, nested in the interface of its level.
+fn select_enum_name(key: &str, selector: &str) -> String {
+    format!("{}{}", to_pascal_case(key), to_pascal_case(selector))
+}
+
+fn enum_constant(case: &str) -> String {
+    to_snake_case(case).to_uppercase()
+}
+
+/// Name of the class with the translations of a language.
+fn class_name(lang: &str) -> String {
+    format!("Trustlate{}", to_pascal_case(lang))
+}
+
+fn make_identifier(name: &str) -> String {
+    escape_keyword(name.to_string(), JAVA_KEYWORDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_java(&trees, "trustlate")
+            .unwrap()
+            .into_iter()
+            .map(|(_, code)| code)
+            .collect::<String>();
+        assert!(code.contains("int n1 = Math.abs(n);"));
+        assert!(code.contains("if (n1 == 1) {"));
+    }
+}
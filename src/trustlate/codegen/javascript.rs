@@ -0,0 +1,160 @@
+use indexmap::IndexMap;
+
+use crate::trustlate::{
+    codegen::{
+        to_pascal_case,
+        typescript::{generate_translations_object, make_typescript_type, object_key},
+    },
+    translations_tree::{LeafType, Parameter, TranslationTreeNode, TranslationsTree},
+};
+
+/// Generates the ES module of a language, the same object as the Typescript target but without
+/// type annotations, which live in the declarations of [`generate_javascript_declarations`].
+pub fn generate_javascript(tree: &TranslationsTree, lang: &str) -> String {
+    format!(
+        "export const trustlate={};\n",
+        generate_translations_object(tree, lang, false)
+    )
+}
+
+/// Generates the `.d.ts` declarations of the ES module of a language, typed as the Typescript
+/// target infers its `as const` object.
+pub fn generate_javascript_declarations(tree: &TranslationsTree) -> String {
+    format!(
+        "export declare const trustlate: {};\n",
+        generate_declarations_rec(&tree.children, 0)
+    )
+}
+
+/// Generates the ES module gathering every language, as the `index.ts` of the Typescript target.
+pub fn generate_javascript_index(langs: &[&str]) -> String {
+    let translations = langs
+        .iter()
+        .map(|lang| format!("\t\"{}\": {},\n", lang, import_name(lang)))
+        .collect::<String>();
+
+    format!(
+        "{}\n{}\n\nconst translations = {{\n{}}};\n\nexport function trustlate(lang) {{ return translations[lang] }}\n",
+        make_imports(langs),
+        make_locales(langs, "export const locales = [{}];"),
+        translations
+    )
+}
+
+/// Generates the `.d.ts` declarations of the index module.
+pub fn generate_javascript_index_declarations(langs: &[&str]) -> String {
+    let translations = langs
+        .iter()
+        .map(|lang| format!("\treadonly \"{}\": typeof {};\n", lang, import_name(lang)))
+        .collect::<String>();
+
+    format!(
+        "{}\n{}\nexport type Locale = typeof locales[number];\n\ndeclare const translations: {{\n{}}};\n\nexport declare function trustlate<L extends Locale>(lang: L): typeof translations[L];\n",
+        make_imports(langs),
+        make_locales(langs, "export declare const locales: readonly [{}];"),
+        translations
+    )
+}
+
+fn import_name(lang: &str) -> String {
+    format!("Translations{}", to_pascal_case(lang))
+}
+
+/// The imports of the language modules, with the extension required by ES modules.
+fn make_imports(langs: &[&str]) -> String {
+    langs
+        .iter()
+        .map(|lang| {
+            format!(
+                "import {{ trustlate as {} }} from \"./{}.js\";\n",
+                import_name(lang),
+                lang
+            )
+        })
+        .collect()
+}
+
+fn make_locales(langs: &[&str], template: &str) -> String {
+    let locales = langs
+        .iter()
+        .map(|lang| format!("\"{}\"", lang))
+        .collect::<Vec<String>>()
+        .join(", ");
+    template.replace("{}", &locales)
+}
+
+fn generate_declarations_rec(
+    children: &IndexMap<String, Box<TranslationTreeNode>>,
+    depth: usize,
+) -> String {
+    let indent = "\t".repeat(depth + 1);
+    let members = children
+        .iter()
+        .map(|(key, node)| {
+            let kind = match node.as_ref() {
+                TranslationTreeNode::Leaf(leaf) => make_leaf_declaration(leaf),
+                TranslationTreeNode::NonLeaf(children) => {
+                    generate_declarations_rec(children, depth + 1)
+                }
+            };
+            format!("{}readonly {}: {};\n", indent, object_key(key), kind)
+        })
+        .collect::<String>();
+    format!("{{\n{}{}}}", members, "\t".repeat(depth))
+}
+
+fn make_leaf_declaration(leaf: &LeafType) -> String {
+    match leaf {
+        LeafType::LiteralLeaf(val) => serde_json::to_string(val).unwrap(),
+        LeafType::ParametrizedLeaf { parameters, .. } => make_function_type(vec![], parameters),
+        LeafType::PluralLeaf {
+            parameter,
+            parameters,
+            ..
+        } => make_function_type(vec![format!("{}: number", parameter)], parameters),
+        LeafType::SelectLeaf {
+            parameter,
+            parameters,
+            cases,
+            ..
+        } => {
+            let selector_type = cases
+                .iter()
+                .map(|(case, _)| format!("\"{}\"", case))
+                .collect::<Vec<String>>()
+                .join(" | ");
+            make_function_type(
+                vec![format!("{}: {}", parameter, selector_type)],
+                parameters,
+            )
+        }
+    }
+}
+
+/// The type of the function of a translation taking `leading` before its `parameters`.
+fn make_function_type(mut leading: Vec<String>, parameters: &[Parameter]) -> String {
+    leading.extend(
+        parameters
+            .iter()
+            .map(|param| format!("{}: {}", param.name, make_typescript_type(param.kind))),
+    );
+    format!("({}) => string", leading.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn declarations_quote_the_keys_that_are_not_identifiers() {
+        let code = generate_javascript_declarations(&tree(r#"{"1st": "First", "my-key": "Mine"}"#));
+        assert!(code.contains("\treadonly \"1st\": \"First\";\n\treadonly \"my-key\": \"Mine\";\n"));
+    }
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let code = generate_javascript(&tree(PLURAL_N), "en");
+        assert!(code.contains("(n)=>{const n1=Math.abs(n);if(n1 == 1)"));
+    }
+}
@@ -0,0 +1,415 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::language_tag,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, form_segments, placeholder_kind, plural_count_variable, select_case_text,
+    select_type_name, to_camel_case, to_pascal_case, type_name, CodegenNode, FormSegment,
+};
+
+/// The `Money` class of the currency placeholders and the functions formatting numbers, amounts and
+/// dates with the `java.text` formatters of a `Locale`, private to the file.
+pub const KOTLIN_HELPERS: &str = r#"data class Money(val amount: Double, val currency: String)
+
+private fun formatNumber(value: Number, locale: Locale): String =
+    NumberFormat.getNumberInstance(locale).format(value)
+
+private fun formatCurrency(value: Money, locale: Locale): String =
+    NumberFormat.getCurrencyInstance(locale)
+        .apply { currency = Currency.getInstance(value.currency) }
+        .format(value.amount)
+
+private fun formatDate(value: Date, locale: Locale): String =
+    DateFormat.getDateInstance(DateFormat.SHORT, locale).format(value)"#;
+
+const KOTLIN_KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+/// Generates a Kotlin file with a sealed interface per level of the translations tree,
+/// implemented by one object per language, and a `getTrustlate` function to pick the
+/// translations of a language.
+///
+/// `trees` starts with the base language, whose translations declare the sealed interfaces and
+/// whose object `getTrustlate` returns for the languages it does not know.
+pub fn generate_kotlin(trees: &[(&String, TranslationsTree)]) -> Result<String, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+
+    let mut code = String::from(
+        "// Code generated by trustlate. DO NOT EDIT.\n@file:Suppress(\"unused\")\n\npackage trustlate\n\nimport java.text.DateFormat\nimport java.text.NumberFormat\nimport java.util.Currency\nimport java.util.Date\nimport java.util.Locale\nimport kotlin.math.abs\n\n",
+    );
+    code += KOTLIN_HELPERS;
+    code += "\n\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "private val {} = Locale.forLanguageTag({})\n",
+            locale_name(lang),
+            kotlin_string(&language_tag(lang))
+        );
+    }
+    code += "\n";
+    generate_select_enums(&mut code, &base_nodes, &[]);
+    generate_interfaces(&mut code, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_objects(&mut code, &nodes, &[], lang);
+    }
+
+    code += "/** The languages of the translations, the first one being the base language. */\n";
+    code += &format!(
+        "val LOCALES: List<String> = listOf({})\n\n",
+        trees
+            .iter()
+            .map(|(lang, _)| kotlin_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    code += "/** Returns the translations of a language, falling back to the base language. */\n";
+    code += "fun getTrustlate(lang: String): Trustlate = when (lang) {\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "    {} -> {}\n",
+            kotlin_string(lang),
+            object_name(&[], lang)
+        );
+    }
+    code += &format!("    else -> {}\n}}\n\n", object_name(&[], base_lang));
+    code += "/**\n * Returns the translations of a locale, falling back to the ones of its language and then to the\n * base language.\n */\n";
+    code += "fun getTrustlate(locale: Locale): Trustlate = when {\n";
+    code += "    locale.toLanguageTag() in LOCALES -> getTrustlate(locale.toLanguageTag())\n";
+    code += "    else -> getTrustlate(locale.language)\n}\n";
+    Ok(code)
+}
+
+fn generate_select_enums(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "enum class {} {{\n",
+                    select_type_name(&[path, &[key]].concat(), parameter)
+                );
+                for (case, _) in cases {
+                    *code += &format!("    {},\n", make_identifier(&to_pascal_case(case)));
+                }
+                *code += "}\n\n";
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_interfaces(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("sealed interface {} {{\n", type_name(path));
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "    val {}: {}\n",
+                    make_identifier(&to_camel_case(key)),
+                    type_name(&[path, &[key]].concat())
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                *code += &format!("    {}\n", make_signature(&[path, &[key]].concat(), base));
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_interfaces(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_objects(code: &mut String, nodes: &[CodegenNode], path: &[&str], lang: &str) {
+    *code += &format!(
+        "object {} : {} {{\n",
+        object_name(path, lang),
+        type_name(path)
+    );
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    override val {}: {}\n        get() = {}\n",
+                    make_identifier(&to_camel_case(key)),
+                    type_name(&child_path),
+                    object_name(&child_path, lang)
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    override {}{}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(&leaf_path, leaf, base, lang)
+                );
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_objects(code, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+/// Declares the member of a translation: a `val` for literal translations, and a `fun` taking its
+/// parameters for the rest of them.
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = make_identifier(&to_camel_case(path.last().unwrap()));
+    let params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) => format!("val {}: String", name),
+        LeafType::ParametrizedLeaf { .. } => {
+            format!("fun {}({}): String", name, params.join(", "))
+        }
+        LeafType::PluralLeaf { parameter, .. } => format!(
+            "fun {}({}): String",
+            name,
+            [
+                vec![format!(
+                    "{}: Int",
+                    make_identifier(&to_camel_case(parameter))
+                )],
+                params
+            ]
+            .concat()
+            .join(", ")
+        ),
+        LeafType::SelectLeaf { parameter, .. } => format!(
+            "fun {}({}): String",
+            name,
+            [
+                vec![format!(
+                    "{}: {}",
+                    make_identifier(&to_camel_case(parameter)),
+                    select_type_name(path, parameter)
+                )],
+                params
+            ]
+            .concat()
+            .join(", ")
+        ),
+    }
+}
+
+fn make_params(params: &[Parameter]) -> Vec<String> {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                "{}: {}",
+                make_identifier(&to_camel_case(&param.name)),
+                make_kotlin_type(param.kind)
+            )
+        })
+        .collect()
+}
+
+fn make_kotlin_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "String",
+        ParameterKind::Number => "Number",
+        ParameterKind::Date => "Date",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds what follows the signature of the member of a translation of `lang`: an expression
+/// body, a `when` over the enum declared from `base` (the base language translation) for selects,
+/// or a block for plurals.
+fn make_body(path: &[&str], leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!(" = {}", kotlin_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => {
+            format!(" = {}", make_form_expression(raw, parameters, None, lang))
+        }
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_camel_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::from(" {\n");
+            if !branches.is_empty() {
+                body += &format!(
+                    "        val {} = abs({})\n",
+                    variable,
+                    make_identifier(&to_camel_case(parameter))
+                );
+            }
+            body += "        return when {\n";
+            for (condition, form) in branches {
+                body += &format!(
+                    "            {} -> {}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body += &format!(
+                "            else -> {}\n        }}\n    }}",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            );
+            body
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let type_name = select_type_name(path, parameter);
+            let mut body = format!(
+                " = when ({}) {{\n",
+                make_identifier(&to_camel_case(parameter))
+            );
+            for (case, _) in base_cases {
+                body += &format!(
+                    "        {}.{} -> {}\n",
+                    type_name,
+                    make_identifier(&to_pascal_case(case)),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + "    }"
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the string template returning a translation, its placeholders being formatted with the
+/// `Locale` of `lang`, and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let locale = locale_name(lang);
+    let template: String = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => escape_kotlin(&text),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_camel_case(&name));
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => format!("${{{}}}", ident),
+                    ParameterKind::Number => format!("${{formatNumber({}, {})}}", ident, locale),
+                    ParameterKind::Date => format!("${{formatDate({}, {})}}", ident, locale),
+                    ParameterKind::Currency => {
+                        format!("${{formatCurrency({}, {})}}", ident, locale)
+                    }
+                }
+            }
+        })
+        .collect();
+    format!("\"{}\"", template)
+}
+
+/// Kotlin string literal of a text.
+fn kotlin_string(text: &str) -> String {
+    format!("\"{}\"", escape_kotlin(text))
+}
+
+fn escape_kotlin(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Name of the object implementing the interface of the level of the tree at `path` for a
+/// language.
+fn object_name(path: &[&str], lang: &str) -> String {
+    format!("{}{}", type_name(path), to_pascal_case(lang))
+}
+
+/// Name of the `java.util.Locale` the translations of a language are formatted with.
+fn locale_name(lang: &str) -> String {
+    format!("locale{}", to_pascal_case(lang))
+}
+
+fn make_identifier(name: &str) -> String {
+    if KOTLIN_KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_kotlin(&trees).unwrap();
+        assert!(code.contains("val n1 = abs(n)"));
+        assert!(code.contains("n1 == 1 ->"));
+    }
+}
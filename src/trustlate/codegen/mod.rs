@@ -1,4 +1,13 @@
+pub mod csharp;
+pub mod dart;
 pub mod go;
+pub mod java;
+pub mod javascript;
+pub mod kotlin;
+pub mod php;
+pub mod python;
+pub mod rust;
+pub mod swift;
 pub mod typescript;
 
 use std::{
@@ -7,51 +16,120 @@ use std::{
     io::Write,
 };
 
-use go::generate_golang;
+use csharp::generate_csharp;
+use dart::generate_dart;
+use go::{generate_golang, GOLANG_HELPERS};
+use java::generate_java;
+use javascript::{
+    generate_javascript, generate_javascript_declarations, generate_javascript_index,
+    generate_javascript_index_declarations,
+};
+use kotlin::generate_kotlin;
+use php::generate_php;
+use python::generate_python;
+use rust::generate_rust;
+use swift::generate_swift;
 use typescript::{generate_typescript_index, genererate_typescript};
 
+use indexmap::IndexMap;
+
 use super::{
     config::{CodegenTarget, Config},
     errors::TrustlateError,
-    translations_tree::TranslationsTree,
+    translations_tree::{
+        find_parameter, placeholders, LeafType, Parameter, ParameterKind, TranslationTreeNode,
+        TranslationsTree, TreePath,
+    },
 };
 
 pub fn generate(
     config: &Config,
     tree: &HashMap<String, TranslationsTree>,
 ) -> Result<(), TrustlateError> {
+    // Languages and translations are laid out as the base language ones, so that the generated
+    // code only changes when the translations do.
+    let base_tree = tree.get(&config.base_lang).unwrap();
+    let tree: Vec<(&String, TranslationsTree)> = config
+        .langs()
+        .map(|lang| (lang, tree.get(lang).unwrap().ordered_as(base_tree)))
+        .collect();
+    // The generators that walk the trees themselves get them with the base language translations
+    // filling in the ones a language lacks, as `codegen_nodes` lays them out.
+    let filled_tree = || -> Result<Vec<(&String, TranslationsTree)>, TrustlateError> {
+        tree.iter()
+            .map(|(lang, translations)| {
+                let nodes = codegen_nodes(&base_tree.children, &translations.children)?;
+                let children = codegen_tree_children(&nodes);
+                Ok((*lang, TranslationsTree { children }))
+            })
+            .collect::<Result<Vec<_>, TrustlateError>>()
+    };
+
     match config.codegen {
         CodegenTarget::Typescript => {
-            for (lang, translations) in tree {
+            for (lang, translations) in &filled_tree()? {
                 let (code, extension) = (
-                    genererate_typescript(translations)
-                        .map_err(|_| TrustlateError::GenerateCannotGenerateCode)?,
+                    genererate_typescript(translations, lang).map_err(|_| {
+                        TrustlateError::GenerateCannotGenerateCode {
+                            lang: lang.to_string(),
+                        }
+                    })?,
                     "ts",
                 );
                 save_translation_file(config, lang, extension, &code)?;
             }
 
-            let mut f =
-                File::create(config.target_dir.join(format!("index.ts"))).map_err(|err| {
-                    println!("Path: {:?}", config.target_dir.join("index.ts"));
-                    eprint!("Error when creating output file: {}", err);
-                    TrustlateError::GenerateCannotCreateOutputFile
-                })?;
-            let mut langs: Vec<&str> = config.target_langs.iter().map(|l| l.as_str()).collect();
-            langs.push(&config.base_lang);
-            write!(f, "{}", generate_typescript_index(&langs))
-                .map_err(|_| TrustlateError::GenerateCannotWriteToOutputFile)?;
+            let langs: Vec<&str> = config.langs().map(|l| l.as_str()).collect();
+            save_translation_file(config, "index", "ts", &generate_typescript_index(&langs))?;
+        }
+        CodegenTarget::Javascript => {
+            for (lang, translations) in &filled_tree()? {
+                save_translation_file(
+                    config,
+                    lang,
+                    "js",
+                    &generate_javascript(translations, lang),
+                )?;
+                save_translation_file(
+                    config,
+                    lang,
+                    "d.ts",
+                    &generate_javascript_declarations(translations),
+                )?;
+            }
+
+            let langs: Vec<&str> = config.langs().map(|l| l.as_str()).collect();
+            save_translation_file(config, "index", "js", &generate_javascript_index(&langs))?;
+            save_translation_file(
+                config,
+                "index",
+                "d.ts",
+                &generate_javascript_index_declarations(&langs),
+            )?;
         }
         CodegenTarget::Go => {
-            let mut content = String::from("package trustlate\n\nimport \"fmt\"\n\n");
+            let mut content = String::from(
+                "package trustlate\n\nimport (\n    \"fmt\"\n    \"strconv\"\n    \"strings\"\n    \"time\"\n)\n\n",
+            );
+            content += format!("{}\n\n", GOLANG_HELPERS).as_str();
             let mut langs: Vec<&String> = vec![];
-            for (lang, translations) in tree {
+            for (lang, translations) in &filled_tree()? {
                 langs.push(lang);
-                let is_main_lang = config.base_lang == *lang;
-                let generations = generate_golang(translations.clone(), lang)
-                    .map_err(|_| TrustlateError::GenerateCannotGenerateCode)?;
+                let is_main_lang = config.base_lang == **lang;
+                let generations = generate_golang(translations.clone(), lang).map_err(|_| {
+                    TrustlateError::GenerateCannotGenerateCode {
+                        lang: lang.to_string(),
+                    }
+                })?;
 
                 if is_main_lang {
+                    for declaration in generations
+                        .iter()
+                        .filter_map(|gen| gen.type_declaration_form())
+                    {
+                        content += format!("{}\n\n", declaration).as_str();
+                    }
+
                     content += "type Trustlate interface {\n";
                     for gen in &generations {
                         content += &("    ".to_string() + &gen.interface_entry_form() + "\n");
@@ -73,8 +151,7 @@ pub fn generate(
                 .as_str();
             }
             content += "\n";
-            content += format!("func GetTrustlate(lang string) Trustlate {{\n    switch lang {{\n")
-                .as_str();
+            content += "func GetTrustlate(lang string) Trustlate {\n    switch lang {\n";
             for lang in &langs {
                 content += format!(
                     "    case \"{lang}\":\n      return &trustlate{}\n",
@@ -84,14 +161,48 @@ pub fn generate(
             }
             content += format!(
                 "    default:\n     return &trustlate{}\n   }}\n}}",
-                langs.first().unwrap().to_uppercase()
+                config.base_lang.to_uppercase()
             )
             .as_str();
 
-            content += format!("\n\nfunc _ignore() {{\n  fmt.Printf(\"ignore\")\n}}").as_str();
+            content += "\n\nfunc _ignore() {\n  fmt.Printf(\"ignore\")\n}";
 
             save_translation_file(config, "trustlate", "go", &content)?;
         }
+        CodegenTarget::Rust => {
+            save_translation_file(config, "trustlate", "rs", &generate_rust(&tree)?)?;
+        }
+        CodegenTarget::Python => {
+            for (stem, code) in generate_python(&tree)? {
+                save_translation_file(config, &stem, "py", &code)?;
+            }
+        }
+        CodegenTarget::Kotlin => {
+            save_translation_file(config, "Trustlate", "kt", &generate_kotlin(&tree)?)?;
+        }
+        CodegenTarget::Swift => {
+            save_translation_file(config, "Trustlate", "swift", &generate_swift(&tree)?)?;
+        }
+        CodegenTarget::Dart => {
+            save_translation_file(config, "trustlate", "dart", &generate_dart(&tree)?)?;
+        }
+        CodegenTarget::CSharp => {
+            let namespace = config.namespace.as_deref().unwrap_or("Trustlate");
+            let code = generate_csharp(&tree, namespace)?;
+            save_translation_file(config, "Trustlate", "cs", &code)?;
+        }
+        CodegenTarget::Java => {
+            let package = config.namespace.as_deref().unwrap_or("trustlate");
+            for (path, code) in generate_java(&tree, package)? {
+                save_translation_file(config, &path, "java", &code)?;
+            }
+        }
+        CodegenTarget::Php => {
+            let namespace = config.namespace.as_deref().unwrap_or("Trustlate");
+            for (stem, code) in generate_php(&tree, namespace)? {
+                save_translation_file(config, &stem, "php", &code)?;
+            }
+        }
     }
     Ok(())
 }
@@ -105,18 +216,425 @@ fn save_translation_file(
     let filepath = config
         .target_dir
         .join(format!("{}.{}", filename_stem, extension));
-    fs::create_dir_all(&filepath.as_path().parent().unwrap())
-        .map_err(|_| TrustlateError::GenerateCannotCreateOutputFolders)?;
+    let folder = filepath.as_path().parent().unwrap();
+    fs::create_dir_all(folder).map_err(|err| {
+        TrustlateError::GenerateCannotCreateOutputFolders {
+            path: folder.to_path_buf(),
+            reason: err.to_string(),
+        }
+    })?;
     let mut f = File::options()
         .write(true)
         .create(true)
         .truncate(true)
         .open(&filepath)
-        .map_err(|err| {
-            println!("Path: {:?}", filepath);
-            eprint!("Error when creating output file: {}", err);
-            TrustlateError::GenerateCannotCreateOutputFile
+        .map_err(|err| TrustlateError::GenerateCannotCreateOutputFile {
+            path: filepath.clone(),
+            reason: err.to_string(),
         })?;
-    write!(f, "{}", code).map_err(|_| TrustlateError::GenerateCannotWriteToOutputFile)?;
+    write!(f, "{}", code).map_err(|err| TrustlateError::GenerateCannotWriteToOutputFile {
+        path: filepath.clone(),
+        reason: err.to_string(),
+    })?;
     Ok(())
 }
+
+/// A node of the translations of a language laid out as the base language ones, which is what the
+/// generators of typed clients walk so that every language provides the same API.
+#[derive(Debug)]
+pub enum CodegenNode<'a> {
+    Group {
+        key: &'a str,
+        children: Vec<CodegenNode<'a>>,
+    },
+    Translation {
+        key: &'a str,
+        /// The translation of the language, or the base language one when the language lacks a
+        /// compatible translation.
+        leaf: &'a LeafType,
+        /// The translation of the base language, which decides the signature.
+        base: &'a LeafType,
+    },
+}
+
+/// Lays out the translations of a language (`children`) as the base language ones (`base`).
+///
+/// Fails when two keys of a level of the base language turn into the same identifier (e.g.
+/// `my-key` and `myKey`), which the generated code could not tell apart.
+pub fn codegen_nodes<'a>(
+    base: &'a IndexMap<String, Box<TranslationTreeNode>>,
+    children: &'a IndexMap<String, Box<TranslationTreeNode>>,
+) -> Result<Vec<CodegenNode<'a>>, TrustlateError> {
+    codegen_nodes_at(base, children, &TreePath::new())
+}
+
+fn codegen_nodes_at<'a>(
+    base: &'a IndexMap<String, Box<TranslationTreeNode>>,
+    children: &'a IndexMap<String, Box<TranslationTreeNode>>,
+    path: &TreePath,
+) -> Result<Vec<CodegenNode<'a>>, TrustlateError> {
+    let words: Vec<Vec<String>> = base
+        .keys()
+        .map(|key| {
+            identifier_words(key)
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect()
+        })
+        .collect();
+    for (idx, key) in base.keys().enumerate() {
+        if let Some(other) = (idx + 1..words.len()).find(|&other| words[other] == words[idx]) {
+            return Err(TrustlateError::GenerateIdentifierCollision {
+                first: path.walk(key),
+                second: path.walk(base.get_index(other).unwrap().0),
+            });
+        }
+    }
+
+    base.iter()
+        .map(|(key, base_node)| {
+            let node = children.get(key).map(|node| &**node);
+            Ok(match (&**base_node, node) {
+                (TranslationTreeNode::NonLeaf(base_children), node) => {
+                    let children = match node {
+                        Some(TranslationTreeNode::NonLeaf(children)) => children,
+                        _ => base_children,
+                    };
+                    CodegenNode::Group {
+                        key,
+                        children: codegen_nodes_at(base_children, children, &path.walk(key))?,
+                    }
+                }
+                (TranslationTreeNode::Leaf(base), node) => {
+                    let leaf = match node {
+                        Some(TranslationTreeNode::Leaf(leaf)) if leaf.is_compatible_with(base) => {
+                            leaf
+                        }
+                        _ => base,
+                    };
+                    CodegenNode::Translation { key, leaf, base }
+                }
+            })
+        })
+        .collect()
+}
+
+/// Turns nodes laid out by [`codegen_nodes`] back into the children of a tree.
+fn codegen_tree_children(nodes: &[CodegenNode]) -> IndexMap<String, Box<TranslationTreeNode>> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            CodegenNode::Group { key, children } => (
+                key.to_string(),
+                Box::new(TranslationTreeNode::NonLeaf(codegen_tree_children(
+                    children,
+                ))),
+            ),
+            CodegenNode::Translation { key, leaf, .. } => (
+                key.to_string(),
+                Box::new(TranslationTreeNode::Leaf((*leaf).clone())),
+            ),
+        })
+        .collect()
+}
+
+/// Name of the type of the level of the tree at `path`, `Trustlate` being the top level one.
+pub fn type_name(path: &[&str]) -> String {
+    match path {
+        [] => "Trustlate".to_string(),
+        _ => path.iter().map(|key| to_pascal_case(key)).collect(),
+    }
+}
+
+/// Name of the type of the selector of the select translation at `path`.
+pub fn select_type_name(path: &[&str], selector: &str) -> String {
+    format!("{}{}", type_name(path), to_pascal_case(selector))
+}
+
+/// Returns the text a select translation has for a case, which is the `other` one when it lacks it.
+pub fn select_case_text<'a>(cases: &'a [(String, String)], case: &str) -> &'a str {
+    cases
+        .iter()
+        .find(|(c, _)| c == case)
+        .or_else(|| cases.iter().find(|(c, _)| c == "other"))
+        .map(|(_, text)| text.as_str())
+        .unwrap_or_default()
+}
+
+/// A piece of a translation: either text or the name of a placeholder.
+#[derive(Debug)]
+pub enum FormSegment {
+    Text(String),
+    Placeholder(String),
+}
+
+pub fn form_segments(form: &str) -> Vec<FormSegment> {
+    let re = regex::Regex::new(r"\{\{(.+?)\}\}").unwrap();
+    let mut segments = vec![];
+    let mut last = 0;
+    for placeholder in re.find_iter(form) {
+        if placeholder.start() > last {
+            segments.push(FormSegment::Text(
+                form[last..placeholder.start()].to_string(),
+            ));
+        }
+        segments.push(FormSegment::Placeholder(
+            placeholders(placeholder.as_str()).remove(0),
+        ));
+        last = placeholder.end();
+    }
+    if last < form.len() {
+        segments.push(FormSegment::Text(form[last..].to_string()));
+    }
+    segments
+}
+
+/// Returns the type of a placeholder, `count` being the count of a plural, which is a number.
+pub fn placeholder_kind(
+    name: &str,
+    parameters: &[Parameter],
+    count: Option<&str>,
+) -> ParameterKind {
+    if Some(name) == count {
+        return ParameterKind::Number;
+    }
+    find_parameter(parameters, name)
+        .map(|param| param.kind)
+        .unwrap_or(ParameterKind::String)
+}
+
+/// Splits a translation key into the words it is made of, be it written in camelCase, snake_case,
+/// kebab-case or with spaces.
+pub fn identifier_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = vec![];
+    let mut current = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[idx - 1];
+            let next_is_lowercase = chars.get(idx + 1).is_some_and(|next| next.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next_is_lowercase)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(|c| c.to_lowercase()))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns the name of the local variable the generated plural methods keep the absolute value of
+/// their count in: `n`, unless one of the placeholders of the plural, named in the generated code
+/// by `identifier`, is already called that way.
+pub fn plural_count_variable(
+    count: &str,
+    parameters: &[Parameter],
+    identifier: impl Fn(&str) -> String,
+) -> String {
+    let taken: Vec<String> = std::iter::once(count)
+        .chain(parameters.iter().map(|param| param.name.as_str()))
+        .map(identifier)
+        .collect();
+    (0..)
+        .map(|idx| match idx {
+            0 => "n".to_string(),
+            idx => format!("n{}", idx),
+        })
+        .find(|name| !taken.contains(name))
+        .unwrap()
+}
+
+/// Appends `_` to an identifier that is one of the `keywords` of a language, as the targets that
+/// cannot quote their keywords escape them.
+pub fn escape_keyword(identifier: String, keywords: &[&str]) -> String {
+    match keywords.contains(&identifier.as_str()) {
+        true => format!("{}_", identifier),
+        false => identifier,
+    }
+}
+
+/// The keywords and literals of Java, which name neither the methods of the Java target nor the
+/// fields of the `R` classes the Android resources turn into.
+pub const JAVA_KEYWORDS: &[&str] = &[
+    "_",
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Makes sure an identifier does not start with a digit nor is empty.
+fn valid_identifier(identifier: String) -> String {
+    match identifier.chars().next() {
+        Some(first) if !first.is_numeric() => identifier,
+        _ => format!("_{}", identifier),
+    }
+}
+
+pub fn to_snake_case(key: &str) -> String {
+    valid_identifier(
+        identifier_words(key)
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_"),
+    )
+}
+
+pub fn to_pascal_case(key: &str) -> String {
+    valid_identifier(
+        identifier_words(key)
+            .iter()
+            .map(|word| capitalize(word))
+            .collect(),
+    )
+}
+
+pub fn to_camel_case(key: &str) -> String {
+    valid_identifier(
+        identifier_words(key)
+            .iter()
+            .enumerate()
+            .map(|(idx, word)| match idx {
+                0 => word.to_lowercase(),
+                _ => capitalize(word),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A plural whose count is named `n`, like the local variable of the generated plural bodies.
+    pub const PLURAL_N: &str = r#"{"items": "{{n, plural, one{{{n}} item} other{{{n}} items}}}"}"#;
+
+    pub fn tree(json: &str) -> TranslationsTree {
+        TranslationsTree::from_json(json).unwrap()
+    }
+
+    #[test]
+    fn plural_count_variable_avoids_the_placeholders() {
+        let parameters = |names: &[&str]| -> Vec<Parameter> {
+            names
+                .iter()
+                .map(|name| Parameter {
+                    name: name.to_string(),
+                    kind: ParameterKind::String,
+                })
+                .collect()
+        };
+        let identity = |name: &str| name.to_string();
+        assert_eq!(plural_count_variable("count", &[], identity), "n");
+        assert_eq!(plural_count_variable("n", &[], identity), "n1");
+        assert_eq!(
+            plural_count_variable("count", &parameters(&["n", "n1"]), identity),
+            "n2"
+        );
+        assert_eq!(plural_count_variable("N", &[], to_camel_case), "n1");
+    }
+
+    #[test]
+    fn codegen_tree_children_fill_in_the_base_language_shape() {
+        let base = tree(r#"{"a": {"b": "B {{x}}"}, "c": "C", "d": "D"}"#);
+        let translations = tree(r#"{"c": {"z": "Z"}, "d": "D {{y}}", "e": "E"}"#);
+        let nodes = codegen_nodes(&base.children, &translations.children).unwrap();
+        let filled = TranslationsTree {
+            children: codegen_tree_children(&nodes),
+        };
+        assert_eq!(
+            serde_json::to_string(&filled).unwrap(),
+            r#"{"a":{"b":"B {{x}}"},"c":"C","d":"D"}"#
+        );
+    }
+
+    #[test]
+    fn codegen_nodes_reject_keys_turning_into_the_same_identifier() {
+        let base = tree(r#"{"a": {"my-key": "A", "b": "B", "myKey": "C"}, "c": "C"}"#);
+        match codegen_nodes(&base.children, &base.children) {
+            Err(TrustlateError::GenerateIdentifierCollision { first, second }) => {
+                assert_eq!(first.steps(), ["a", "my-key"]);
+                assert_eq!(second.steps(), ["a", "myKey"]);
+            }
+            other => panic!("unexpected layout {:?}", other),
+        }
+    }
+
+    #[test]
+    fn escape_keyword_only_changes_the_keywords() {
+        assert_eq!(escape_keyword("class".to_string(), JAVA_KEYWORDS), "class_");
+        assert_eq!(escape_keyword("title".to_string(), JAVA_KEYWORDS), "title");
+    }
+}
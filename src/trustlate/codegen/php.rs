@@ -0,0 +1,538 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::locale_formats,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, form_segments, placeholder_kind, plural_count_variable, select_case_text,
+    select_type_name, to_camel_case, to_pascal_case, type_name, CodegenNode, FormSegment,
+};
+
+/// The `TrustlateFormat` class, written to its own file, whose static functions format the numbers,
+/// amounts and dates of the placeholders with the separators and patterns of each language.
+pub const PHP_HELPERS: &str = r#"final class TrustlateFormat
+{
+    public static function number(int|float $value, string $decimalSeparator, string $groupSeparator): string
+    {
+        $decimals = 0;
+        if (is_float($value) && floor($value) != $value) {
+            $decimals = strlen(rtrim(explode('.', sprintf('%.10F', $value))[1], '0'));
+        }
+        return number_format($value, $decimals, $decimalSeparator, $groupSeparator);
+    }
+
+    public static function currency(Money $value, string $decimalSeparator, string $groupSeparator, bool $currencyFirst): string
+    {
+        $amount = number_format($value->amount, 2, $decimalSeparator, $groupSeparator);
+        return $currencyFirst ? $value->currency . ' ' . $amount : $amount . ' ' . $value->currency;
+    }
+}"#;
+
+/// The `Money` class of the currency placeholders, written to `Money.php`.
+pub const PHP_MONEY: &str = r#"/** An amount of money in a currency, given by its ISO 4217 code. */
+final class Money
+{
+    public function __construct(
+        public readonly float $amount,
+        public readonly string $currency,
+    ) {
+    }
+}"#;
+
+/// Names PHP does not allow for classes, interfaces and enums, in lowercase.
+const PHP_RESERVED_NAMES: &[&str] = &[
+    "abstract",
+    "and",
+    "array",
+    "as",
+    "bool",
+    "break",
+    "callable",
+    "case",
+    "catch",
+    "class",
+    "clone",
+    "const",
+    "continue",
+    "declare",
+    "default",
+    "do",
+    "echo",
+    "else",
+    "elseif",
+    "empty",
+    "enddeclare",
+    "endfor",
+    "endforeach",
+    "endif",
+    "endswitch",
+    "endwhile",
+    "enum",
+    "eval",
+    "exit",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "fn",
+    "for",
+    "foreach",
+    "function",
+    "global",
+    "goto",
+    "if",
+    "implements",
+    "include",
+    "instanceof",
+    "insteadof",
+    "int",
+    "interface",
+    "isset",
+    "iterable",
+    "list",
+    "match",
+    "mixed",
+    "namespace",
+    "never",
+    "new",
+    "null",
+    "object",
+    "or",
+    "parent",
+    "print",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "require",
+    "return",
+    "self",
+    "static",
+    "string",
+    "switch",
+    "throw",
+    "trait",
+    "true",
+    "try",
+    "unset",
+    "use",
+    "var",
+    "void",
+    "while",
+    "xor",
+    "yield",
+];
+
+/// A generated PHP file, as its stem and its code.
+pub type PhpFile = (String, String);
+
+/// Generates a file per class, interface and enum of a namespace, named after them as PSR-4
+/// expects:
+/// - `Trustlate.php` and an interface per group of translations.
+/// - A class per language and level of the tree implementing them.
+/// - An enum per select translation, `Money.php` and `TrustlateFormat.php`.
+/// - `TrustlateFactory.php`, with the `forLocale` entry point.
+///
+/// `trees` starts with the base language, whose translations declare the interfaces and enums
+/// and which `TrustlateFactory::forLocale` falls back to.
+pub fn generate_php(
+    trees: &[(&String, TranslationsTree)],
+    namespace: &str,
+) -> Result<Vec<PhpFile>, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+    let header = format!(
+        "<?php\n\n// Code generated by trustlate. DO NOT EDIT.\n\ndeclare(strict_types=1);\n\nnamespace {};\n\n",
+        namespace
+    );
+    let mut files = vec![
+        ("Money".to_string(), format!("{}{}\n", header, PHP_MONEY)),
+        (
+            "TrustlateFormat".to_string(),
+            format!("{}{}\n", header, PHP_HELPERS),
+        ),
+    ];
+
+    generate_select_enums(&mut files, &header, &base_nodes, &[]);
+    generate_interfaces(&mut files, &header, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_classes(&mut files, &header, &nodes, &[], lang);
+    }
+
+    let mut factory = header + "final class TrustlateFactory\n{\n";
+    factory +=
+        "    /** The languages of the translations, the first one being the base language. */\n";
+    factory += &format!(
+        "    public const LOCALES = [{}];\n\n",
+        trees
+            .iter()
+            .map(|(lang, _)| php_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    factory += "    /**\n     * Returns the translations of a locale (e.g. `pt-BR` or `pt_BR`), falling back to the ones of its language\n     * and then to the base language.\n     */\n";
+    factory += "    public static function forLocale(string $locale): Trustlate\n    {\n";
+    factory += "        $locale = str_replace('_', '-', $locale);\n";
+    factory += "        $lang = in_array($locale, self::LOCALES, true) ? $locale : explode('-', $locale)[0];\n\n";
+    factory += "        return match ($lang) {\n";
+    for (lang, _) in trees {
+        factory += &format!(
+            "            {} => new {}(),\n",
+            php_string(lang),
+            class_name(&[], lang)
+        );
+    }
+    factory += &format!(
+        "            default => new {}(),\n        }};\n    }}\n}}\n",
+        class_name(&[], base_lang)
+    );
+    files.push(("TrustlateFactory".to_string(), factory));
+
+    Ok(files)
+}
+
+fn generate_select_enums(
+    files: &mut Vec<PhpFile>,
+    header: &str,
+    nodes: &[CodegenNode],
+    path: &[&str],
+) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(files, header, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                let name = select_type_name(&[path, &[key]].concat(), parameter);
+                let mut code = format!("{}enum {}: string\n{{\n", header, name);
+                for (case, _) in cases {
+                    code += &format!(
+                        "    case {} = {};\n",
+                        make_case_name(case),
+                        php_string(case)
+                    );
+                }
+                code += "}\n";
+                files.push((name, code));
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_interfaces(
+    files: &mut Vec<PhpFile>,
+    header: &str,
+    nodes: &[CodegenNode],
+    path: &[&str],
+) {
+    let name = interface_name(path);
+    let mut code = format!("{}interface {}\n{{\n", header, name);
+    let members: Vec<String> = nodes
+        .iter()
+        .map(|node| match node {
+            CodegenNode::Group { key, .. } => format!(
+                "    public function {}(): {};\n",
+                to_camel_case(key),
+                interface_name(&[path, &[key]].concat())
+            ),
+            CodegenNode::Translation { key, base, .. } => {
+                format!("    {};\n", make_signature(&[path, &[key]].concat(), base))
+            }
+        })
+        .collect();
+    code += &members.join("\n");
+    code += "}\n";
+    files.push((name, code));
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_interfaces(files, header, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_classes(
+    files: &mut Vec<PhpFile>,
+    header: &str,
+    nodes: &[CodegenNode],
+    path: &[&str],
+    lang: &str,
+) {
+    let name = class_name(path, lang);
+    let mut code = format!(
+        "{}final class {} implements {}\n{{\n",
+        header,
+        name,
+        interface_name(path)
+    );
+    let members: Vec<String> = nodes
+        .iter()
+        .map(|node| match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                format!(
+                    "    public function {}(): {}\n    {{\n        return new {}();\n    }}\n",
+                    to_camel_case(key),
+                    interface_name(&child_path),
+                    class_name(&child_path, lang)
+                )
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                format!(
+                    "    {}\n    {{\n{}    }}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(&leaf_path, leaf, base, lang)
+                )
+            }
+        })
+        .collect();
+    code += &members.join("\n");
+    code += "}\n";
+    files.push((name, code));
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_classes(files, header, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = to_camel_case(path.last().unwrap());
+    let mut params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) | LeafType::ParametrizedLeaf { .. } => {}
+        LeafType::PluralLeaf { parameter, .. } => {
+            params.insert(0, format!("int {}", make_variable(parameter)))
+        }
+        LeafType::SelectLeaf { parameter, .. } => params.insert(
+            0,
+            format!(
+                "{} {}",
+                select_type_name(path, parameter),
+                make_variable(parameter)
+            ),
+        ),
+    }
+    format!("public function {}({}): string", name, params.join(", "))
+}
+
+fn make_params(params: &[Parameter]) -> Vec<String> {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                "{} {}",
+                make_php_type(param.kind),
+                make_variable(&param.name)
+            )
+        })
+        .collect()
+}
+
+fn make_php_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "string",
+        ParameterKind::Number => "int|float",
+        ParameterKind::Date => "\\DateTimeInterface",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds the statements of the method of a translation of `lang`, selects returning a `match`
+/// over the cases of the enum declared from `base`, the base language translation.
+fn make_body(path: &[&str], leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!("        return {};\n", php_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => format!(
+            "        return {};\n",
+            make_form_expression(raw, parameters, None, lang)
+        ),
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = format!(
+                "${}",
+                plural_count_variable(parameter, parameters, |name| {
+                    make_variable(name)[1..].to_string()
+                })
+            );
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::new();
+            if !branches.is_empty() {
+                body += &format!(
+                    "        {} = abs({});\n",
+                    variable,
+                    make_variable(parameter)
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "        if ({}) {{\n            return {};\n        }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body + &format!(
+                "        return {};\n",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            )
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let type_name = select_type_name(path, parameter);
+            let mut body = format!("        return match ({}) {{\n", make_variable(parameter));
+            for (case, _) in base_cases {
+                body += &format!(
+                    "            {}::{} => {},\n",
+                    type_name,
+                    make_case_name(case),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + "        };\n"
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the `.` concatenation returning a translation, formatting its placeholders with the
+/// separators and date pattern of `lang` and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let formats = locale_formats(lang);
+    let (decimal, group) = (
+        php_string(formats.decimal_separator),
+        php_string(formats.group_separator),
+    );
+    let parts: Vec<String> = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => php_string(&text),
+            FormSegment::Placeholder(name) => {
+                let variable = make_variable(&name);
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => variable,
+                    ParameterKind::Number => format!(
+                        "TrustlateFormat::number({}, {}, {})",
+                        variable, decimal, group
+                    ),
+                    ParameterKind::Date => format!(
+                        "{}->format({})",
+                        variable,
+                        php_string(&php_date_format(formats.date_pattern))
+                    ),
+                    ParameterKind::Currency => format!(
+                        "TrustlateFormat::currency({}, {}, {}, {})",
+                        variable, decimal, group, formats.currency_first
+                    ),
+                }
+            }
+        })
+        .collect();
+
+    if parts.is_empty() {
+        "''".to_string()
+    } else {
+        parts.join(" . ")
+    }
+}
+
+/// Turns a date pattern of the locale tables into the format `DateTimeInterface::format` takes.
+fn php_date_format(pattern: &str) -> String {
+    pattern
+        .replace("yyyy", "Y")
+        .replace("MM", "m")
+        .replace("dd", "d")
+}
+
+/// PHP single quoted string literal of a text.
+fn php_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Name of the interface of the level of the tree at `path`, suffixed when PHP reserves it (e.g.
+/// `List_`).
+fn interface_name(path: &[&str]) -> String {
+    make_type_name(type_name(path))
+}
+
+/// Name of the final class of a language implementing the interface of the level of the tree at
+/// `path`, which never clashes with a reserved name as it ends with the language.
+fn class_name(path: &[&str], lang: &str) -> String {
+    format!("{}{}", type_name(path), to_pascal_case(lang))
+}
+
+fn make_type_name(name: String) -> String {
+    if PHP_RESERVED_NAMES.contains(&name.to_lowercase().as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn make_case_name(case: &str) -> String {
+    match to_pascal_case(case).as_str() {
+        // `Enum::class` is the name of the enum, so it cannot be one of its cases.
+        "Class" => "Class_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn make_variable(name: &str) -> String {
+    match to_camel_case(name).as_str() {
+        "this" => "$this_".to_string(),
+        name => format!("${}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_php(&trees, "Trustlate")
+            .unwrap()
+            .into_iter()
+            .map(|(_, code)| code)
+            .collect::<String>();
+        assert!(code.contains("$n1 = abs($n);"));
+        assert!(code.contains("if ($n1 == 1) {"));
+    }
+}
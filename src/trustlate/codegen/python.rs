@@ -0,0 +1,430 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::locale_formats,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, escape_keyword, form_segments, placeholder_kind, plural_count_variable,
+    select_case_text, select_type_name, to_pascal_case, to_snake_case, type_name, CodegenNode,
+    FormSegment,
+};
+
+/// The `Money` dataclass of the currency placeholders and the `format_*` functions the classes of
+/// every language call, written to `_base.py` along with the protocols.
+pub const PYTHON_HELPERS: &str = r#"@dataclass(frozen=True)
+class Money:
+    amount: float
+    currency: str
+
+
+def format_number(value: float, decimal_separator: str, group_separator: str) -> str:
+    if isinstance(value, float) and value.is_integer():
+        value = int(value)
+    return _separate(f"{value:,}", decimal_separator, group_separator)
+
+
+def format_currency(value: Money, decimal_separator: str, group_separator: str, currency_first: bool) -> str:
+    amount = _separate(f"{value.amount:,.2f}", decimal_separator, group_separator)
+    return f"{value.currency} {amount}" if currency_first else f"{amount} {value.currency}"
+
+
+def format_date(value: datetime.date, pattern: str) -> str:
+    return (
+        pattern.replace("yyyy", f"{value.year:04d}")
+        .replace("MM", f"{value.month:02d}")
+        .replace("dd", f"{value.day:02d}")
+    )
+
+
+def _separate(number: str, decimal_separator: str, group_separator: str) -> str:
+    return number.replace(",", "\0").replace(".", decimal_separator).replace("\0", group_separator)"#;
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// A generated Python file, as its stem and its code.
+pub type PythonModule = (String, String);
+
+/// Generates a Python package made of:
+/// - `_base.py`, with a `Protocol` per level of the translations tree and the types they use.
+/// - A module per language, with a class implementing every protocol.
+/// - `__init__.py`, with the `get_trustlate(lang)` factory.
+///
+/// The base language comes first in `trees`: its translations shape the protocols, and
+/// `get_trustlate` falls back to it.
+pub fn generate_python(
+    trees: &[(&String, TranslationsTree)],
+) -> Result<Vec<PythonModule>, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+    let mut modules = vec![];
+
+    let mut base = String::from(
+        "# Code generated by trustlate. DO NOT EDIT.\nfrom __future__ import annotations\n\nimport datetime\nfrom dataclasses import dataclass\nfrom typing import Literal, Protocol\n\n\n",
+    );
+    base += PYTHON_HELPERS;
+    base += "\n\n\n";
+    generate_select_aliases(&mut base, &base_nodes, &[]);
+    generate_protocols(&mut base, &base_nodes, &[]);
+    modules.push(("_base".to_string(), base.trim_end().to_string() + "\n"));
+
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        let mut code = String::from(
+            "# Code generated by trustlate. DO NOT EDIT.\nfrom __future__ import annotations\n\nimport datetime\n\nfrom ._base import *  # noqa: F403\n\n\n",
+        );
+        generate_classes(&mut code, &nodes, &[], lang);
+        modules.push((module_name(lang), code.trim_end().to_string() + "\n"));
+    }
+
+    let mut init = String::from(
+        "# Code generated by trustlate. DO NOT EDIT.\nfrom ._base import Money, Trustlate\n",
+    );
+    for (lang, _) in trees {
+        init += &format!(
+            "from .{} import {}\n",
+            module_name(lang),
+            class_name(&[], lang)
+        );
+    }
+    init += &format!(
+        "\n__all__ = [\"LOCALES\", \"Money\", \"Trustlate\", \"get_trustlate\"]\n\n# The languages of the translations, the first one being the base language.\nLOCALES = ({},)\n\n_TRUSTLATES: dict[str, Trustlate] = {{\n",
+        trees
+            .iter()
+            .map(|(lang, _)| python_string(lang))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    for (lang, _) in trees {
+        init += &format!(
+            "    {}: {}(),\n",
+            python_string(lang),
+            class_name(&[], lang)
+        );
+    }
+    init += &format!(
+        "}}\n\n\ndef get_trustlate(lang: str) -> Trustlate:\n    \"\"\"Returns the translations of a language, falling back to the base language.\"\"\"\n    return _TRUSTLATES.get(lang, _TRUSTLATES[{}])\n",
+        python_string(base_lang)
+    );
+    modules.push(("__init__".to_string(), init));
+
+    Ok(modules)
+}
+
+fn generate_select_aliases(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_aliases(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "{} = Literal[{}]\n\n\n",
+                    select_type_name(&[path, &[key]].concat(), parameter),
+                    cases
+                        .iter()
+                        .map(|(case, _)| python_string(case))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_protocols(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("class {}(Protocol):\n", type_name(path));
+    if nodes.is_empty() {
+        *code += "    pass\n";
+    }
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "    @property\n    def {}(self) -> {}: ...\n\n",
+                    make_identifier(&to_snake_case(key)),
+                    type_name(&[path, &[key]].concat())
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                *code += &format!(
+                    "    {}: ...\n\n",
+                    make_signature(&[path, &[key]].concat(), base)
+                );
+            }
+        }
+    }
+    *code = code.trim_end().to_string() + "\n\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_protocols(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_classes(code: &mut String, nodes: &[CodegenNode], path: &[&str], lang: &str) {
+    *code += &format!("class {}:\n", class_name(path, lang));
+    if nodes.is_empty() {
+        *code += "    pass\n";
+    }
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    @property\n    def {}(self) -> {}:\n        return {}()\n\n",
+                    make_identifier(&to_snake_case(key)),
+                    type_name(&child_path),
+                    class_name(&child_path, lang)
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    {}:\n{}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(leaf, base, lang)
+                );
+            }
+        }
+    }
+    *code = code.trim_end().to_string() + "\n\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_classes(code, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+/// Declares the method of a translation: a property for literal translations, and a method
+/// taking its parameters as keyword arguments for the rest of them.
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = make_identifier(&to_snake_case(path.last().unwrap()));
+    let params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) => format!("@property\n    def {}(self) -> str", name),
+        LeafType::ParametrizedLeaf { .. } => format!("def {}(self{}) -> str", name, params),
+        LeafType::PluralLeaf { parameter, .. } => format!(
+            "def {}(self, {}: int{}) -> str",
+            name,
+            make_identifier(&to_snake_case(parameter)),
+            params
+        ),
+        LeafType::SelectLeaf { parameter, .. } => format!(
+            "def {}(self, {}: {}{}) -> str",
+            name,
+            make_identifier(&to_snake_case(parameter)),
+            select_type_name(path, parameter),
+            params
+        ),
+    }
+}
+
+fn make_params(params: &[Parameter]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: String = params
+        .iter()
+        .map(|param| {
+            format!(
+                ", {}: {}",
+                make_identifier(&to_snake_case(&param.name)),
+                make_python_type(param.kind)
+            )
+        })
+        .collect();
+    format!(", *{}", params)
+}
+
+fn make_python_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "str",
+        ParameterKind::Number => "float",
+        ParameterKind::Date => "datetime.date",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds the indented body of the method of a translation of `lang`, where plurals and selects
+/// become chains of `if` statements, the latter over the cases of `base`, the base language
+/// translation.
+fn make_body(leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!("        return {}\n", python_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => format!(
+            "        return {}\n",
+            make_form_expression(raw, parameters, None, lang)
+        ),
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_snake_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::new();
+            if !branches.is_empty() {
+                body += &format!(
+                    "        {} = abs({})\n",
+                    variable,
+                    make_identifier(&to_snake_case(parameter))
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "        if {}:\n            return {}\n",
+                    make_python_condition(&condition),
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body + &format!(
+                "        return {}\n",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            )
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let selector = make_identifier(&to_snake_case(parameter));
+            let mut body = String::new();
+            for (case, _) in base_cases.iter().filter(|(case, _)| case != "other") {
+                body += &format!(
+                    "        if {} == {}:\n            return {}\n",
+                    selector,
+                    python_string(case),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + &format!(
+                "        return {}\n",
+                make_form_expression(select_case_text(cases, "other"), parameters, None, lang)
+            )
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Turns a plural rule condition, written with C operators, into a Python one.
+fn make_python_condition(condition: &str) -> String {
+    condition.replace("&&", "and").replace("||", "or")
+}
+
+/// Builds the `+` concatenation returning a translation, its placeholders being formatted by the
+/// `format_*` helpers with the separators of `lang`, and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let formats = locale_formats(lang);
+    let (decimal, group) = (
+        python_string(formats.decimal_separator),
+        python_string(formats.group_separator),
+    );
+    let parts: Vec<String> = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => python_string(&text),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_snake_case(&name));
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => ident,
+                    ParameterKind::Number => {
+                        format!("format_number({}, {}, {})", ident, decimal, group)
+                    }
+                    ParameterKind::Date => format!(
+                        "format_date({}, {})",
+                        ident,
+                        python_string(formats.date_pattern)
+                    ),
+                    ParameterKind::Currency => format!(
+                        "format_currency({}, {}, {}, {})",
+                        ident,
+                        decimal,
+                        group,
+                        if formats.currency_first {
+                            "True"
+                        } else {
+                            "False"
+                        }
+                    ),
+                }
+            }
+        })
+        .collect();
+
+    if parts.is_empty() {
+        "\"\"".to_string()
+    } else {
+        parts.join(" + ")
+    }
+}
+
+/// Python string literal of a text, which is the same as its JSON string.
+fn python_string(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+/// Name of the class implementing the protocol of the level of the tree at `path` for a language.
+fn class_name(path: &[&str], lang: &str) -> String {
+    format!("{}{}", type_name(path), to_pascal_case(lang))
+}
+
+fn module_name(lang: &str) -> String {
+    make_identifier(&to_snake_case(lang))
+}
+
+fn make_identifier(name: &str) -> String {
+    escape_keyword(name.to_string(), PYTHON_KEYWORDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_python(&trees)
+            .unwrap()
+            .into_iter()
+            .map(|(_, code)| code)
+            .collect::<String>();
+        assert!(code.contains("n1 = abs(n)"));
+        assert!(code.contains("if n1 == 1:"));
+    }
+}
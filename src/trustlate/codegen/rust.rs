@@ -0,0 +1,437 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::locale_formats,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, form_segments, placeholder_kind, plural_count_variable, select_case_text,
+    select_type_name, to_pascal_case, to_snake_case, type_name, CodegenNode, FormSegment,
+};
+
+/// The `Money` and `Date` types of the currency and date placeholders, and the `format_*` functions
+/// the trait implementations call, pasted at the top of the module so that it has no dependencies.
+pub const RUST_HELPERS: &str = r#"#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Money<'a> {
+    pub amount: f64,
+    pub currency: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+fn format_number(value: f64, decimal_separator: &str, group_separator: &str) -> String {
+    group_digits(&value.to_string(), decimal_separator, group_separator)
+}
+
+fn format_currency(
+    value: &Money,
+    decimal_separator: &str,
+    group_separator: &str,
+    currency_first: bool,
+) -> String {
+    let amount = group_digits(&format!("{:.2}", value.amount), decimal_separator, group_separator);
+    if currency_first {
+        format!("{} {}", value.currency, amount)
+    } else {
+        format!("{} {}", amount, value.currency)
+    }
+}
+
+fn format_date(value: Date, pattern: &str) -> String {
+    pattern
+        .replace("yyyy", &format!("{:04}", value.year))
+        .replace("MM", &format!("{:02}", value.month))
+        .replace("dd", &format!("{:02}", value.day))
+}
+
+fn group_digits(number: &str, decimal_separator: &str, group_separator: &str) -> String {
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", number),
+    };
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(group_separator);
+        }
+        grouped.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal_separator, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}"#;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Generates a Rust module with a trait per level of the translations tree, implemented by one
+/// struct per language, and a `Locale` enum to pick the translations of a language.
+///
+/// The first of `trees` is the base language, whose translations shape the traits and which
+/// `Locale::from_code` falls back to.
+pub fn generate_rust(trees: &[(&String, TranslationsTree)]) -> Result<String, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+
+    let mut code = String::from(
+        "// Code generated by trustlate. DO NOT EDIT.\n#![allow(dead_code, clippy::all)]\n\n",
+    );
+    code += RUST_HELPERS;
+    code += "\n\n";
+    generate_select_enums(&mut code, &base_nodes, &[]);
+    generate_traits(&mut code, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_impls(&mut code, &nodes, &[], lang);
+    }
+
+    let locales: Vec<(String, &str)> = trees
+        .iter()
+        .map(|(lang, _)| (to_pascal_case(lang), lang.as_str()))
+        .collect();
+    let base_locale = to_pascal_case(base_lang);
+    code += "/// The languages of the translations, the first one being the base language.\n";
+    code += "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum Locale {\n";
+    for (variant, _) in &locales {
+        code += &format!("    {},\n", variant);
+    }
+    code += "}\n\nimpl Locale {\n";
+    code += &format!(
+        "    pub const ALL: [Locale; {}] = [{}];\n\n",
+        locales.len(),
+        locales
+            .iter()
+            .map(|(variant, _)| format!("Locale::{}", variant))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    code +=
+        "    /// Returns the language code of the locale, as it is written in the configuration.\n";
+    code += "    pub fn code(self) -> &'static str {\n        match self {\n";
+    for (variant, lang) in &locales {
+        code += &format!("            Locale::{} => {:?},\n", variant, lang);
+    }
+    code += "        }\n    }\n\n";
+    code += "    /// Returns the locale of a language code, falling back to the base language.\n";
+    code += "    pub fn from_code(code: &str) -> Locale {\n        match code {\n";
+    for (variant, lang) in &locales {
+        code += &format!("            {:?} => Locale::{},\n", lang, variant);
+    }
+    code += &format!(
+        "            _ => Locale::{},\n        }}\n    }}\n\n",
+        base_locale
+    );
+    code += "    pub fn trustlate(self) -> &'static dyn Trustlate {\n        match self {\n";
+    for (variant, _) in &locales {
+        code += &format!(
+            "            Locale::{} => &{},\n",
+            variant,
+            impl_name(&[], variant)
+        );
+    }
+    code += "        }\n    }\n}\n\n";
+    code += "pub fn get_trustlate(lang: &str) -> &'static dyn Trustlate {\n    Locale::from_code(lang).trustlate()\n}\n";
+    Ok(code)
+}
+
+fn generate_select_enums(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum {} {{\n",
+                    select_type_name(&[path, &[key]].concat(), parameter)
+                );
+                for (case, _) in cases {
+                    *code += &format!("    {},\n", to_pascal_case(case));
+                }
+                *code += "}\n\n";
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_traits(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("pub trait {} {{\n", type_name(path));
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "    fn {}(&self) -> &'static dyn {};\n",
+                    make_identifier(&to_snake_case(key)),
+                    type_name(&[path, &[key]].concat())
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                *code += &format!("    {};\n", make_signature(&[path, &[key]].concat(), base));
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_traits(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+fn generate_impls(code: &mut String, nodes: &[CodegenNode], path: &[&str], lang: &str) {
+    let struct_name = impl_name(path, &to_pascal_case(lang));
+    *code += &format!(
+        "pub struct {};\n\nimpl {} for {} {{\n",
+        struct_name,
+        type_name(path),
+        struct_name
+    );
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                let child_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    fn {}(&self) -> &'static dyn {} {{\n        &{}\n    }}\n",
+                    make_identifier(&to_snake_case(key)),
+                    type_name(&child_path),
+                    impl_name(&child_path, &to_pascal_case(lang))
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "    {} {{\n{}    }}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(&leaf_path, leaf, base, lang)
+                );
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_impls(code, children, &[path, &[key]].concat(), lang);
+        }
+    }
+}
+
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = make_identifier(&to_snake_case(path.last().unwrap()));
+    let params = make_params(base.parameters());
+    match base {
+        LeafType::LiteralLeaf(_) => format!("fn {}(&self) -> &'static str", name),
+        LeafType::ParametrizedLeaf { .. } => format!("fn {}(&self{}) -> String", name, params),
+        LeafType::PluralLeaf { parameter, .. } => format!(
+            "fn {}(&self, {}: i64{}) -> String",
+            name,
+            make_identifier(&to_snake_case(parameter)),
+            params
+        ),
+        LeafType::SelectLeaf { parameter, .. } => format!(
+            "fn {}(&self, {}: {}{}) -> String",
+            name,
+            make_identifier(&to_snake_case(parameter)),
+            select_type_name(path, parameter),
+            params
+        ),
+    }
+}
+
+fn make_params(params: &[Parameter]) -> String {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                ", {}: {}",
+                make_identifier(&to_snake_case(&param.name)),
+                make_rust_type(param.kind)
+            )
+        })
+        .collect()
+}
+
+fn make_rust_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "&str",
+        ParameterKind::Number => "f64",
+        ParameterKind::Date => "Date",
+        ParameterKind::Currency => "Money<'_>",
+    }
+}
+
+/// Builds the tail expression of the method of a translation of `lang`: an `if` chain for
+/// plurals, and a `match` over the enum declared from `base`, the base language translation, for
+/// selects.
+fn make_body(path: &[&str], leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!("        {:?}\n", text),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => {
+            format!(
+                "        {}\n",
+                make_form_expression(raw, parameters, None, lang)
+            )
+        }
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let count = make_identifier(&to_snake_case(parameter));
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_snake_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::new();
+            if !branches.is_empty() {
+                body += &format!("        let {} = {}.abs();\n", variable, count);
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "        if {} {{\n            return {};\n        }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body += &format!(
+                "        {}\n",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            );
+            body
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let type_name = select_type_name(path, parameter);
+            let mut body = format!(
+                "        match {} {{\n",
+                make_identifier(&to_snake_case(parameter))
+            );
+            for (case, _) in base_cases {
+                body += &format!(
+                    "            {}::{} => {},\n",
+                    type_name,
+                    to_pascal_case(case),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + "        }\n"
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the `format!` call returning a translation, or a `String::from` when it has no
+/// placeholders, formatting them with the separators of `lang` and the `count` of a plural as a
+/// number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let formats = locale_formats(lang);
+    let (decimal, group) = (formats.decimal_separator, formats.group_separator);
+    let mut template = String::new();
+    let mut args = vec![];
+    for segment in form_segments(form) {
+        match segment {
+            FormSegment::Text(text) => template += &text.replace('{', "{{").replace('}', "}}"),
+            FormSegment::Placeholder(name) => {
+                template += "{}";
+                let ident = make_identifier(&to_snake_case(&name));
+                args.push(if Some(name.as_str()) == count {
+                    format!(
+                        "format_number({} as f64, {:?}, {:?})",
+                        ident, decimal, group
+                    )
+                } else {
+                    match placeholder_kind(&name, params, count) {
+                        ParameterKind::String => ident,
+                        ParameterKind::Number => {
+                            format!("format_number({}, {:?}, {:?})", ident, decimal, group)
+                        }
+                        ParameterKind::Date => {
+                            format!("format_date({}, {:?})", ident, formats.date_pattern)
+                        }
+                        ParameterKind::Currency => format!(
+                            "format_currency(&{}, {:?}, {:?}, {})",
+                            ident, decimal, group, formats.currency_first
+                        ),
+                    }
+                });
+            }
+        }
+    }
+
+    if args.is_empty() {
+        format!("String::from({:?})", form)
+    } else {
+        format!("format!({:?}, {})", template, args.join(", "))
+    }
+}
+
+/// Name of the struct implementing the trait of the level of the tree at `path` for a language.
+fn impl_name(path: &[&str], lang_type: &str) -> String {
+    format!("{}{}", type_name(path), lang_type)
+}
+
+fn make_identifier(name: &str) -> String {
+    match name {
+        "self" | "super" | "crate" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_rust(&trees).unwrap();
+        assert!(code.contains("let n1 = n.abs();"));
+        assert!(code.contains("if n1 == 1 {"));
+    }
+}
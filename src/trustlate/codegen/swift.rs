@@ -0,0 +1,510 @@
+use crate::trustlate::{
+    errors::TrustlateError,
+    locales::language_tag,
+    plurals::plural_branches,
+    translations_tree::{LeafType, Parameter, ParameterKind, TranslationsTree},
+};
+
+use super::{
+    codegen_nodes, form_segments, placeholder_kind, plural_count_variable, select_case_text,
+    select_type_name, to_camel_case, to_pascal_case, type_name, CodegenNode, FormSegment,
+};
+
+/// The `Money` struct of the currency placeholders and the private functions formatting numbers,
+/// amounts and dates with the Foundation formatters of a `Locale`.
+pub const SWIFT_HELPERS: &str = r#"public struct Money {
+    public let amount: Double
+    public let currency: String
+
+    public init(amount: Double, currency: String) {
+        self.amount = amount
+        self.currency = currency
+    }
+}
+
+private func formatNumber(_ value: Double, _ locale: Locale) -> String {
+    let formatter = NumberFormatter()
+    formatter.locale = locale
+    formatter.numberStyle = .decimal
+    return formatter.string(from: NSNumber(value: value)) ?? String(value)
+}
+
+private func formatCurrency(_ value: Money, _ locale: Locale) -> String {
+    let formatter = NumberFormatter()
+    formatter.locale = locale
+    formatter.numberStyle = .currency
+    formatter.currencyCode = value.currency
+    return formatter.string(from: NSNumber(value: value.amount)) ?? "\(value.amount) \(value.currency)"
+}
+
+private func formatDate(_ value: Date, _ locale: Locale) -> String {
+    let formatter = DateFormatter()
+    formatter.locale = locale
+    formatter.dateStyle = .short
+    formatter.timeStyle = .none
+    return formatter.string(from: value)
+}"#;
+
+const SWIFT_KEYWORDS: &[&str] = &[
+    "Any",
+    "Self",
+    "as",
+    "associatedtype",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "deinit",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "fileprivate",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "open",
+    "operator",
+    "precedencegroup",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "rethrows",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "subscript",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typealias",
+    "var",
+    "where",
+    "while",
+];
+
+/// Generates a Swift file with a protocol per level of the translations tree, implemented by one
+/// struct per language whose nested structs mirror the groups of translations, and a `Trustlate`
+/// enum to pick the translations of a language with `Trustlate.for(locale:)`.
+///
+/// Swift cannot call static functions on a protocol, so the top level protocol is `Translations`
+/// and the `Trustlate` name is left to the enum.
+///
+/// `trees` lists the base language first, whose translations declare the protocols and whose case
+/// is the last fallback of `Trustlate.for(locale:)`.
+pub fn generate_swift(trees: &[(&String, TranslationsTree)]) -> Result<String, TrustlateError> {
+    let (base_lang, base_tree) = &trees[0];
+    let base_nodes = codegen_nodes(&base_tree.children, &base_tree.children)?;
+
+    let mut code =
+        String::from("// Code generated by trustlate. DO NOT EDIT.\nimport Foundation\n\n");
+    code += SWIFT_HELPERS;
+    code += "\n\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "private let {} = Locale(identifier: {})\n",
+            locale_name(lang),
+            swift_string(&language_tag(lang))
+        );
+    }
+    code += "\n";
+    generate_select_enums(&mut code, &base_nodes, &[]);
+    generate_protocols(&mut code, &base_nodes, &[]);
+    for (lang, tree) in trees {
+        let nodes = codegen_nodes(&base_tree.children, &tree.children)?;
+        generate_structs(&mut code, &nodes, &[], lang, "");
+        code += "\n";
+    }
+
+    code += "/// The languages of the translations, the first one being the base language.\n";
+    code += "public enum Trustlate: String, CaseIterable {\n";
+    for (lang, _) in trees {
+        code += &format!(
+            "    case {} = {}\n",
+            make_identifier(&to_camel_case(lang)),
+            swift_string(lang)
+        );
+    }
+    code += &format!(
+        "\n    public var translations: any {} {{\n        switch self {{\n",
+        protocol_name(&[])
+    );
+    for (lang, _) in trees {
+        code += &format!(
+            "        case .{}: return {}()\n",
+            make_identifier(&to_camel_case(lang)),
+            struct_name(lang)
+        );
+    }
+    code += "        }\n    }\n\n";
+    code += "    /// Returns the translations of a locale identifier (e.g. `pt-BR` or `pt_BR`), falling back to the ones of its\n    /// language and then to the base language.\n";
+    code += &format!(
+        "    public static func `for`(locale: String) -> any {} {{\n",
+        protocol_name(&[])
+    );
+    code += "        let identifier = locale.replacingOccurrences(of: \"_\", with: \"-\")\n";
+    code += "        let language = String(identifier.prefix { $0 != \"-\" })\n";
+    code += &format!(
+        "        return (Trustlate(rawValue: identifier) ?? Trustlate(rawValue: language) ?? .{}).translations\n    }}\n\n",
+        make_identifier(&to_camel_case(base_lang))
+    );
+    code += &format!(
+        "    public static func `for`(locale: Locale) -> any {} {{\n        self.for(locale: locale.identifier)\n    }}\n}}\n",
+        protocol_name(&[])
+    );
+    Ok(code)
+}
+
+fn generate_select_enums(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, children } => {
+                generate_select_enums(code, children, &[path, &[key]].concat())
+            }
+            CodegenNode::Translation {
+                key,
+                base:
+                    LeafType::SelectLeaf {
+                        parameter, cases, ..
+                    },
+                ..
+            } => {
+                *code += &format!(
+                    "public enum {} {{\n",
+                    select_type_name(&[path, &[key]].concat(), parameter)
+                );
+                for (case, _) in cases {
+                    *code += &format!("    case {}\n", make_identifier(&to_camel_case(case)));
+                }
+                *code += "}\n\n";
+            }
+            CodegenNode::Translation { .. } => {}
+        }
+    }
+}
+
+fn generate_protocols(code: &mut String, nodes: &[CodegenNode], path: &[&str]) {
+    *code += &format!("public protocol {} {{\n", protocol_name(path));
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "    var {}: any {} {{ get }}\n",
+                    make_identifier(&to_camel_case(key)),
+                    protocol_name(&[path, &[key]].concat())
+                );
+            }
+            CodegenNode::Translation { key, base, .. } => {
+                let signature = make_signature(&[path, &[key]].concat(), base);
+                match base {
+                    LeafType::LiteralLeaf(_) => *code += &format!("    {} {{ get }}\n", signature),
+                    _ => *code += &format!("    {}\n", signature),
+                }
+            }
+        }
+    }
+    *code += "}\n\n";
+
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            generate_protocols(code, children, &[path, &[key]].concat());
+        }
+    }
+}
+
+/// Declares the struct implementing the protocol of the level of the tree at `path` for a
+/// language, nesting the structs of the groups it has.
+fn generate_structs(
+    code: &mut String,
+    nodes: &[CodegenNode],
+    path: &[&str],
+    lang: &str,
+    indent: &str,
+) {
+    let name = match path.last() {
+        Some(key) => to_pascal_case(key),
+        None => struct_name(lang),
+    };
+    *code += &format!(
+        "{indent}public struct {}: {} {{\n{indent}    public init() {{}}\n",
+        name,
+        protocol_name(path)
+    );
+    for node in nodes {
+        match node {
+            CodegenNode::Group { key, .. } => {
+                *code += &format!(
+                    "\n{indent}    public var {}: any {} {{ {}() }}\n",
+                    make_identifier(&to_camel_case(key)),
+                    protocol_name(&[path, &[key]].concat()),
+                    to_pascal_case(key)
+                );
+            }
+            CodegenNode::Translation { key, leaf, base } => {
+                let leaf_path = [path, &[key]].concat();
+                *code += &format!(
+                    "\n{indent}    public {} {{\n{}{indent}    }}\n",
+                    make_signature(&leaf_path, base),
+                    make_body(leaf, base, lang)
+                        .lines()
+                        .map(|line| format!("{indent}{line}\n"))
+                        .collect::<String>()
+                );
+            }
+        }
+    }
+    for node in nodes {
+        if let CodegenNode::Group { key, children } = node {
+            *code += "\n";
+            generate_structs(
+                code,
+                children,
+                &[path, &[key]].concat(),
+                lang,
+                &format!("{indent}    "),
+            );
+        }
+    }
+    *code += &format!("{indent}}}\n");
+}
+
+/// Declares the member of a translation: a `var` for literal translations, and a `func` taking its
+/// parameters as labeled arguments for the rest of them.
+fn make_signature(path: &[&str], base: &LeafType) -> String {
+    let name = make_identifier(&to_camel_case(path.last().unwrap()));
+    let params = make_params(base.parameters());
+    let params = match base {
+        LeafType::LiteralLeaf(_) => return format!("var {}: String", name),
+        LeafType::ParametrizedLeaf { .. } => params,
+        LeafType::PluralLeaf { parameter, .. } => [
+            vec![format!(
+                "{}: Int",
+                make_identifier(&to_camel_case(parameter))
+            )],
+            params,
+        ]
+        .concat(),
+        LeafType::SelectLeaf { parameter, .. } => [
+            vec![format!(
+                "{}: {}",
+                make_identifier(&to_camel_case(parameter)),
+                select_type_name(path, parameter)
+            )],
+            params,
+        ]
+        .concat(),
+    };
+    format!("func {}({}) -> String", name, params.join(", "))
+}
+
+fn make_params(params: &[Parameter]) -> Vec<String> {
+    params
+        .iter()
+        .map(|param| {
+            format!(
+                "{}: {}",
+                make_identifier(&to_camel_case(&param.name)),
+                make_swift_type(param.kind)
+            )
+        })
+        .collect()
+}
+
+fn make_swift_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "String",
+        ParameterKind::Number => "Double",
+        ParameterKind::Date => "Date",
+        ParameterKind::Currency => "Money",
+    }
+}
+
+/// Builds the statements of the property or function of a translation of `lang`, selects
+/// `switch`ing over the cases of the enum declared from `base`, the base language translation.
+fn make_body(leaf: &LeafType, base: &LeafType, lang: &str) -> String {
+    match (leaf, base) {
+        (LeafType::LiteralLeaf(text), _) => format!("        {}\n", swift_string(text)),
+        (LeafType::ParametrizedLeaf { parameters, raw }, _) => format!(
+            "        {}\n",
+            make_form_expression(raw, parameters, None, lang)
+        ),
+        (
+            LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            },
+            _,
+        ) => {
+            let variable = plural_count_variable(parameter, parameters, |name| {
+                make_identifier(&to_camel_case(name))
+            });
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let mut body = String::new();
+            if !branches.is_empty() {
+                body += &format!(
+                    "        let {} = abs({})\n",
+                    variable,
+                    make_identifier(&to_camel_case(parameter))
+                );
+            }
+            for (condition, form) in branches {
+                body += &format!(
+                    "        if {} {{\n            return {}\n        }}\n",
+                    condition,
+                    make_form_expression(form, parameters, Some(parameter), lang)
+                );
+            }
+            body + &format!(
+                "        return {}\n",
+                make_form_expression(other, parameters, Some(parameter), lang)
+            )
+        }
+        (
+            LeafType::SelectLeaf {
+                parameters, cases, ..
+            },
+            LeafType::SelectLeaf {
+                parameter,
+                cases: base_cases,
+                ..
+            },
+        ) => {
+            let mut body = format!(
+                "        switch {} {{\n",
+                make_identifier(&to_camel_case(parameter))
+            );
+            for (case, _) in base_cases {
+                body += &format!(
+                    "        case .{}: return {}\n",
+                    make_identifier(&to_camel_case(case)),
+                    make_form_expression(select_case_text(cases, case), parameters, None, lang)
+                );
+            }
+            body + "        }\n"
+        }
+        // Compatible leaves are always of the same kind.
+        (LeafType::SelectLeaf { .. }, _) => unreachable!(),
+    }
+}
+
+/// Builds the string literal returning a translation, interpolating its placeholders formatted
+/// with the `Locale` of `lang`, and the `count` of a plural as a number.
+fn make_form_expression(
+    form: &str,
+    params: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let locale = locale_name(lang);
+    let template: String = form_segments(form)
+        .into_iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => escape_swift(&text),
+            FormSegment::Placeholder(name) => {
+                let ident = make_identifier(&to_camel_case(&name));
+                if Some(name.as_str()) == count {
+                    return format!("\\(formatNumber(Double({}), {}))", ident, locale);
+                }
+                match placeholder_kind(&name, params, count) {
+                    ParameterKind::String => format!("\\({})", ident),
+                    ParameterKind::Number => format!("\\(formatNumber({}, {}))", ident, locale),
+                    ParameterKind::Date => format!("\\(formatDate({}, {}))", ident, locale),
+                    ParameterKind::Currency => {
+                        format!("\\(formatCurrency({}, {}))", ident, locale)
+                    }
+                }
+            }
+        })
+        .collect();
+    format!("\"{}\"", template)
+}
+
+/// Swift string literal of a text.
+fn swift_string(text: &str) -> String {
+    format!("\"{}\"", escape_swift(text))
+}
+
+fn escape_swift(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Name of the protocol of the level of the tree at `path`, prefixed so that it does not clash
+/// with the structs nested in the ones of every language. The top level one is `Translations`, as
+/// `Trustlate` names the enum of the languages.
+fn protocol_name(path: &[&str]) -> String {
+    match path {
+        [] => "Translations".to_string(),
+        _ => format!("Trustlate{}", type_name(path)),
+    }
+}
+
+/// Name of the struct with the translations of a language.
+fn struct_name(lang: &str) -> String {
+    format!("Trustlate{}", to_pascal_case(lang))
+}
+
+/// Name of the `Locale` the translations of a language are formatted with.
+fn locale_name(lang: &str) -> String {
+    format!("locale{}", to_pascal_case(lang))
+}
+
+fn make_identifier(name: &str) -> String {
+    if SWIFT_KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let (en, ru) = ("en".to_string(), "ru".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&ru, tree(PLURAL_N))];
+        let code = generate_swift(&trees).unwrap();
+        assert!(code.contains("let n1 = abs(n)"));
+        assert!(code.contains("if n1 == 1 {"));
+    }
+
+    #[test]
+    fn translations_are_picked_with_trustlate_for_locale() {
+        let (en, pt_br) = ("en".to_string(), "pt-BR".to_string());
+        let trees = [(&en, tree(PLURAL_N)), (&pt_br, tree(PLURAL_N))];
+        let code = generate_swift(&trees).unwrap();
+        assert!(code.contains("public protocol Translations {\n"));
+        assert!(code.contains("public struct TrustlatePtBr: Translations {\n"));
+        assert!(code.contains("public enum Trustlate: String, CaseIterable {\n    case en = \"en\"\n    case ptBr = \"pt-BR\"\n"));
+        assert!(code.contains("public static func `for`(locale: String) -> any Translations {\n"));
+        assert!(code.contains("public static func `for`(locale: Locale) -> any Translations {\n"));
+    }
+}
@@ -1,15 +1,35 @@
 use crate::trustlate::{
+    codegen::plural_count_variable,
     errors::TrustlateError,
-    translations_tree::{TranslationTreeNode, TranslationsTree},
+    locales::language_tag,
+    plurals::plural_branches,
+    translations_tree::{
+        find_parameter, placeholders, LeafType, Parameter, ParameterKind, TranslationTreeNode,
+        TranslationsTree,
+    },
 };
 
-pub fn genererate_typescript(tree: &TranslationsTree) -> Result<String, TrustlateError> {
+pub fn genererate_typescript(
+    tree: &TranslationsTree,
+    lang: &str,
+) -> Result<String, TrustlateError> {
     let suffix = "export const trustlate=";
     let prefix = "as const;";
+    Ok(format!(
+        "{}{}{}",
+        suffix,
+        generate_translations_object(tree, lang, true),
+        prefix
+    ))
+}
+
+/// Builds the object literal holding the translations of a language, with the types of the
+/// parameters of its functions when it is `typed`.
+pub fn generate_translations_object(tree: &TranslationsTree, lang: &str, typed: bool) -> String {
     let top_level_nodes: Vec<String> = tree
         .children
         .iter()
-        .map(|(k, v)| generate_typescript_rec(k, v))
+        .map(|(k, v)| generate_typescript_rec(k, v, lang, typed))
         .collect();
     let mut aux = "".to_string();
     for (i, child_code) in top_level_nodes.iter().enumerate() {
@@ -18,8 +38,7 @@ pub fn genererate_typescript(tree: &TranslationsTree) -> Result<String, Trustlat
             aux = format!("{},", aux);
         }
     }
-    aux = format!("{{{}}}", aux);
-    Ok(format!("{}{}{}", suffix, aux, prefix))
+    format!("{{{}}}", aux)
 }
 
 pub fn generate_typescript_index(langs: &[&str]) -> String {
@@ -52,13 +71,24 @@ pub fn generate_typescript_index(langs: &[&str]) -> String {
     format!("{}\n{}\nexport type Locale = typeof locales[number];\n\n{}\n\nexport function trustlate(lang: keyof typeof translations) {{ return translations[lang] }}", imports,locales,translations)
 }
 
-fn generate_typescript_rec(key: &String, curr_node: &Box<TranslationTreeNode>) -> String {
-    match &**curr_node {
-        TranslationTreeNode::Leaf(value) => format!("{}:{}", key, value),
+fn generate_typescript_rec(
+    key: &str,
+    curr_node: &TranslationTreeNode,
+    lang: &str,
+    typed: bool,
+) -> String {
+    match curr_node {
+        TranslationTreeNode::Leaf(value) => {
+            format!(
+                "{}:{}",
+                object_key(key),
+                generate_typescript_leaf(value, lang, typed)
+            )
+        }
         TranslationTreeNode::NonLeaf(children) => {
             let children_code: Vec<String> = children
                 .iter()
-                .map(|(k, v)| generate_typescript_rec(k, v))
+                .map(|(k, v)| generate_typescript_rec(k, v, lang, typed))
                 .collect();
             let mut aux = "".to_string();
             for (i, child_code) in children_code.iter().enumerate() {
@@ -67,7 +97,208 @@ fn generate_typescript_rec(key: &String, curr_node: &Box<TranslationTreeNode>) -
                     aux = format!("{},", aux);
                 }
             }
-            format!("{}:{{{}}}", key, aux)
+            format!("{}:{{{}}}", object_key(key), aux)
+        }
+    }
+}
+
+fn generate_typescript_leaf(leaf: &LeafType, lang: &str, typed: bool) -> String {
+    let arg = |name: &str, kind: &str| match typed {
+        true => format!("{}:{}", name, kind),
+        false => name.to_string(),
+    };
+    match leaf {
+        LeafType::LiteralLeaf(val) => serde_json::to_string(val).unwrap(),
+        LeafType::ParametrizedLeaf { parameters, raw } => {
+            let args = parameters.iter().fold("".to_string(), |acc, el| {
+                format!("{}{},", acc, arg(&el.name, make_typescript_type(el.kind)))
+            });
+            // remove the final ","
+            let args = args.strip_suffix(',').unwrap();
+            format!(
+                "({})=>{}",
+                args,
+                make_template_literal(raw, parameters, None, lang)
+            )
+        }
+        LeafType::PluralLeaf {
+            parameter,
+            parameters,
+            forms,
+            ..
+        } => {
+            let args = parameters.iter().fold(arg(parameter, "number"), |acc, el| {
+                format!("{},{}", acc, arg(&el.name, make_typescript_type(el.kind)))
+            });
+            let variable = plural_count_variable(parameter, parameters, str::to_string);
+            let (branches, other) = plural_branches(lang, forms, &variable);
+            let body = branches
+                .iter()
+                .fold("".to_string(), |acc, (condition, form)| {
+                    format!(
+                        "{}if({})return {};",
+                        acc,
+                        condition,
+                        make_template_literal(form, parameters, Some(parameter), lang)
+                    )
+                });
+            format!(
+                "({})=>{{const {}=Math.abs({});{}return {}}}",
+                args,
+                variable,
+                parameter,
+                body,
+                make_template_literal(other, parameters, Some(parameter), lang)
+            )
+        }
+        LeafType::SelectLeaf {
+            parameter,
+            parameters,
+            cases,
+            ..
+        } => {
+            let selector_type = cases
+                .iter()
+                .map(|(case, _)| format!("\"{}\"", case))
+                .collect::<Vec<String>>()
+                .join("|");
+            let args = parameters
+                .iter()
+                .fold(arg(parameter, &selector_type), |acc, el| {
+                    format!("{},{}", acc, arg(&el.name, make_typescript_type(el.kind)))
+                });
+            let mut body = "".to_string();
+            let mut other = "";
+            for (case, text) in cases {
+                if case == "other" {
+                    other = text;
+                } else {
+                    body = format!(
+                        "{}case \"{}\":return {};",
+                        body,
+                        case,
+                        make_template_literal(text, parameters, None, lang)
+                    );
+                }
+            }
+            format!(
+                "({})=>{{switch({}){{{}default:return {}}}}}",
+                args,
+                parameter,
+                body,
+                make_template_literal(other, parameters, None, lang)
+            )
         }
     }
 }
+
+pub fn make_typescript_type(kind: ParameterKind) -> &'static str {
+    match kind {
+        ParameterKind::String => "string",
+        ParameterKind::Number => "number",
+        ParameterKind::Date => "Date",
+        ParameterKind::Currency => "{amount:number,currency:string}",
+    }
+}
+
+/// Writes a key of an object literal or type, quoted when it is not an identifier (e.g. `1st`).
+pub fn object_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    match is_identifier {
+        true => key.to_string(),
+        false => serde_json::to_string(key).unwrap(),
+    }
+}
+
+/// Turns a translation into a template literal with its `{{...}}` placeholders interpolated and
+/// formatted with the `Intl` API of the language, `count` being the count of a plural.
+///
+/// The rest of the text is escaped so that backslashes, backticks and `${` are written as is.
+fn make_template_literal(
+    translation: &str,
+    parameters: &[Parameter],
+    count: Option<&str>,
+    lang: &str,
+) -> String {
+    let re = regex::Regex::new(r"\{\{(.+?)\}\}").unwrap();
+    let tag = language_tag(lang);
+    let mut body = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(translation) {
+        let placeholder = caps.get(0).unwrap();
+        body += &escape_template_text(&translation[last..placeholder.start()]);
+        body += &make_interpolation(&caps[0], parameters, count, &tag);
+        last = placeholder.end();
+    }
+    body += &escape_template_text(&translation[last..]);
+    format!("`{}`", body)
+}
+
+fn escape_template_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+/// The `${...}` interpolation of a `{{...}}` placeholder of a translation.
+fn make_interpolation(
+    placeholder: &str,
+    parameters: &[Parameter],
+    count: Option<&str>,
+    tag: &str,
+) -> String {
+    let name = placeholders(placeholder).remove(0);
+    let kind = if Some(name.as_str()) == count {
+        ParameterKind::Number
+    } else {
+        find_parameter(parameters, &name)
+            .map(|param| param.kind)
+            .unwrap_or(ParameterKind::String)
+    };
+    match kind {
+        ParameterKind::String => format!("${{{}}}", name),
+        ParameterKind::Number => {
+            format!("${{new Intl.NumberFormat(\"{}\").format({})}}", tag, name)
+        }
+        ParameterKind::Date => {
+            format!("${{new Intl.DateTimeFormat(\"{}\").format({})}}", tag, name)
+        }
+        ParameterKind::Currency => format!(
+            "${{new Intl.NumberFormat(\"{}\",{{style:\"currency\",currency:{}.currency}}).format({}.amount)}}",
+            tag, name, name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::codegen::tests::{tree, PLURAL_N};
+
+    #[test]
+    fn keys_that_are_not_identifiers_are_quoted() {
+        let code = genererate_typescript(
+            &tree(r#"{"1st": "First", "my-key": {"a_b": "AB"}, "$ok": "OK"}"#),
+            "en",
+        )
+        .unwrap();
+        assert!(code.contains(r#"{"1st":"First","my-key":{a_b:"AB"},$ok:"OK"}"#));
+    }
+
+    #[test]
+    fn template_literals_escape_the_text() {
+        let code =
+            genererate_typescript(&tree(r#"{"a": "Use `x` \\ ${x} {{name}}"}"#), "en").unwrap();
+        assert!(code.contains(r#"(name:string)=>`Use \`x\` \\ \${x} ${name}`"#));
+    }
+
+    #[test]
+    fn plural_count_does_not_shadow_a_placeholder_named_n() {
+        let code = genererate_typescript(&tree(PLURAL_N), "en").unwrap();
+        assert!(code.contains("(n:number)=>{const n1=Math.abs(n);if(n1 == 1)"));
+    }
+}
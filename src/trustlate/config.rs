@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs::{self, File},
-    io::Write,
     path::{Path, PathBuf},
 };
 
-use super::errors::TrustlateError;
+use super::{
+    errors::{json_error_reason, SourceSpan, TrustlateError},
+    reports::Severity,
+    translations_tree::{locate_key, TreeComparisonDifference},
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
@@ -14,46 +18,118 @@ pub struct Config {
     pub codegen: CodegenTarget,
     pub source_dir: PathBuf,
     pub target_dir: PathBuf,
+    /// Text `fix` fills the missing translations with, `[FILLING]` by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filling: Option<String>,
+    /// Severity of the differences `check` finds, by kind of difference (e.g.
+    /// `"extra-node": "error"`), overriding the default ones.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub severities: BTreeMap<String, Severity>,
+    /// Namespace (C#, PHP) or package (Java) of the generated code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
 }
 
 impl Config {
     pub fn from_file(filepath: &Path) -> Result<Self, TrustlateError> {
-        let f = File::open(filepath).map_err(|_| TrustlateError::OpenConfigFile)?;
-        serde_json::from_reader(f).map_err(|_| TrustlateError::ParseConfigFile)
+        let source =
+            fs::read_to_string(filepath).map_err(|err| TrustlateError::OpenConfigFile {
+                path: filepath.to_path_buf(),
+                reason: err.to_string(),
+            })?;
+        let config: Config =
+            serde_json::from_str(&source).map_err(|err| TrustlateError::ParseConfigFile {
+                path: filepath.to_path_buf(),
+                reason: json_error_reason(&err),
+                span: SourceSpan::from_json_error(&source, &err),
+            })?;
+
+        if let Some(kind) = config
+            .severities
+            .keys()
+            .find(|kind| !TreeComparisonDifference::KINDS.contains(&kind.as_str()))
+        {
+            return Err(TrustlateError::ParseConfigFile {
+                path: filepath.to_path_buf(),
+                reason: format!(
+                    "unknown kind of difference `{}` in `severities`, expected one of: {}",
+                    kind,
+                    TreeComparisonDifference::KINDS.join(", ")
+                ),
+                span: locate_key(&source, &["severities".to_string(), kind.to_string()])
+                    .and_then(|offset| SourceSpan::at_offset(&source, offset)),
+            });
+        }
+        Ok(config)
     }
 
-    pub fn initialize(&self) -> Result<(), TrustlateError> {
-        let config_file =
-            File::create(".trustlaterc.json").map_err(|_| TrustlateError::InitCreateConfigFile)?;
-        serde_json::to_writer_pretty(config_file, &self)
-            .map_err(|_| TrustlateError::InitWriteConfigFile)?;
-        fs::create_dir_all(&self.source_dir).map_err(|_| TrustlateError::InitCreateSourceDir)?;
-        fs::create_dir_all(&self.target_dir).map_err(|_| TrustlateError::InitCreateTargetDir)?;
+    pub fn filling(&self) -> &str {
+        self.filling.as_deref().unwrap_or("[FILLING]")
+    }
 
-        let base_file = File::create(self.source_dir.join(format!("{}.json", self.base_lang)))
-            .map_err(|_| TrustlateError::InitCreateTranslationsFile)?;
-        serde_json::to_writer_pretty(base_file, &serde_json::json!({"examples": { "helloWorld": "Hola, Mundo!", "greeting": "Encantado de conocerte {{name}}!" }})).map_err(|_| TrustlateError::InitWriteTranslationsExample)?;
+    /// Returns the severity of a difference found by `check`.
+    pub fn severity_of(&self, difference: &TreeComparisonDifference) -> Severity {
+        self.severities
+            .get(difference.kind())
+            .copied()
+            .unwrap_or_else(|| Severity::default_for(difference))
+    }
 
-        let base_file = File::create(
-            self.source_dir
-                .join(format!("{}.json", self.target_langs[0])),
-        )
-        .map_err(|_| TrustlateError::InitCreateTranslationsFile)?;
-        serde_json::to_writer_pretty(base_file, &serde_json::json!({"examples": { "helloWorld": "헬로, 월드!", "greeting": "{{name}} 만나서  방아워요!" }})).map_err(|_| TrustlateError::InitWriteTranslationsExample)?;
+    /// Returns the base language followed by the target languages, in the order they are configured.
+    pub fn langs(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.base_lang).chain(self.target_langs.iter())
+    }
+
+    pub fn initialize(&self) -> Result<(), TrustlateError> {
+        let config_path = Path::new(".trustlaterc.json");
+        let config_file =
+            File::create(config_path).map_err(|err| TrustlateError::InitCreateConfigFile {
+                path: config_path.to_path_buf(),
+                reason: err.to_string(),
+            })?;
+        serde_json::to_writer_pretty(config_file, &self).map_err(|err| {
+            TrustlateError::InitWriteConfigFile {
+                path: config_path.to_path_buf(),
+                reason: err.to_string(),
+            }
+        })?;
+        fs::create_dir_all(&self.source_dir).map_err(|err| {
+            TrustlateError::InitCreateSourceDir {
+                path: self.source_dir.clone(),
+                reason: err.to_string(),
+            }
+        })?;
+        fs::create_dir_all(&self.target_dir).map_err(|err| {
+            TrustlateError::InitCreateTargetDir {
+                path: self.target_dir.clone(),
+                reason: err.to_string(),
+            }
+        })?;
 
-        let base_file = File::create(
-            self.source_dir
-                .join(format!("{}.json", self.target_langs[1])),
-        )
-        .map_err(|_| TrustlateError::InitCreateTranslationsFile)?;
-        serde_json::to_writer_pretty(
-            base_file,
+        self.write_example(&self.base_lang, &serde_json::json!({"examples": { "helloWorld": "Hola, Mundo!", "greeting": "Encantado de conocerte {{name}}!" }}))?;
+        self.write_example(&self.target_langs[0], &serde_json::json!({"examples": { "helloWorld": "헬로, 월드!", "greeting": "{{name}} 만나서  방아워요!" }}))?;
+        self.write_example(
+            &self.target_langs[1],
             &serde_json::json!({"examples": { "helloWorld": "Hello, World!", "greeting": "Nice to meet you {{name}}" }}),
-        )
-        .map_err(|_| TrustlateError::InitWriteTranslationsExample)?;
+        )?;
 
         Ok(())
     }
+
+    fn write_example(&self, lang: &str, example: &serde_json::Value) -> Result<(), TrustlateError> {
+        let path = self.source_dir.join(format!("{}.json", lang));
+        let file =
+            File::create(&path).map_err(|err| TrustlateError::InitCreateTranslationsFile {
+                path: path.clone(),
+                reason: err.to_string(),
+            })?;
+        serde_json::to_writer_pretty(file, example).map_err(|err| {
+            TrustlateError::InitWriteTranslationsExample {
+                path,
+                reason: err.to_string(),
+            }
+        })
+    }
 }
 
 impl Default for Config {
@@ -64,6 +140,9 @@ impl Default for Config {
             codegen: CodegenTarget::Typescript,
             source_dir: Path::new("./trustlate/translations/").to_path_buf(),
             target_dir: Path::new("./trustlate/codegens/").to_path_buf(),
+            filling: None,
+            severities: BTreeMap::new(),
+            namespace: None,
         }
     }
 }
@@ -74,8 +153,35 @@ pub enum CodegenTarget {
     #[default]
     #[serde(rename = "ts", alias = "typescript")]
     Typescript,
+    /// Generate ES modules with their Typescript declarations
+    #[serde(rename = "js", alias = "javascript")]
+    Javascript,
     #[serde(rename = "go", alias = "golang")]
     Go,
+    /// Generate a Rust module
+    #[serde(rename = "rust", alias = "rs")]
+    Rust,
+    /// Generate a Python package
+    #[serde(rename = "python", alias = "py")]
+    Python,
+    /// Generate a Kotlin file, e.g. for Android apps
+    #[serde(rename = "kotlin", alias = "kt")]
+    Kotlin,
+    /// Generate a Swift file, e.g. for iOS apps
+    #[serde(rename = "swift")]
+    Swift,
+    /// Generate a Dart library, e.g. for Flutter apps
+    #[serde(rename = "dart")]
+    Dart,
+    /// Generate a C# file
+    #[serde(rename = "csharp", alias = "cs")]
+    CSharp,
+    /// Generate the Java sources of a package
+    #[serde(rename = "java")]
+    Java,
+    /// Generate the PHP classes of a PSR-4 namespace
+    #[serde(rename = "php")]
+    Php,
 }
 
 // pub struct Config2<'a> {
@@ -1,20 +1,387 @@
+use std::{fmt::Display, path::PathBuf};
+
+use colored::*;
+
+use super::translations_tree::TreePath;
+
+/// A line of a source file together with the line and column (both starting at 1) an error
+/// points to.
+#[derive(Debug)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+impl SourceSpan {
+    pub fn new(source: &str, line: usize, column: usize) -> Option<Self> {
+        let text = source.lines().nth(line.checked_sub(1)?)?.to_string();
+        Some(Self { line, column, text })
+    }
+
+    /// Returns the span of the byte at `offset` of `source`.
+    pub fn at_offset(source: &str, offset: usize) -> Option<Self> {
+        let before = source.get(..offset)?;
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        Self::new(source, line, column)
+    }
+
+    /// Returns the span of a serde_json error, if it points to a line of `source`.
+    pub fn from_json_error(source: &str, error: &serde_json::Error) -> Option<Self> {
+        Self::new(source, error.line(), error.column().max(1))
+    }
+}
+
+/// Returns the message of a serde_json error without the location it appends to it.
+pub fn json_error_reason(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(idx) if error.line() > 0 => message[..idx].to_string(),
+        _ => message,
+    }
+}
+
+/// The reason a translations file could not be turned into a translations tree.
+#[derive(Debug)]
+pub struct InvalidTranslations {
+    pub reason: String,
+    pub tree_path: Option<TreePath>,
+    pub span: Option<SourceSpan>,
+}
+
 #[derive(Debug)]
 pub enum TrustlateError {
-    OpenConfigFile,
-    ParseConfigFile,
-    InitCreateConfigFile,
-    InitWriteConfigFile,
-    InitCreateSourceDir,
-    InitCreateTargetDir,
-    InitCreateTranslationsFile,
-    InitWriteTranslationsExample,
-    ParseTranslationFileCannotOpen,
-    ParseTraslationFileInvalidJson,
-    ParseTranslationFileRepeatedLanguageKey,
-    FixTreeCannotOpenSourceFile,
-    FixTreeCannotWriteToSourceFile,
-    GenerateCannotCreateOutputFile,
-    GenerateCannotCreateOutputFolders,
-    GenerateCannotWriteToOutputFile,
-    GenerateCannotGenerateCode,
+    OpenConfigFile {
+        path: PathBuf,
+        reason: String,
+    },
+    ParseConfigFile {
+        path: PathBuf,
+        reason: String,
+        span: Option<SourceSpan>,
+    },
+    InitCreateConfigFile {
+        path: PathBuf,
+        reason: String,
+    },
+    InitWriteConfigFile {
+        path: PathBuf,
+        reason: String,
+    },
+    InitCreateSourceDir {
+        path: PathBuf,
+        reason: String,
+    },
+    InitCreateTargetDir {
+        path: PathBuf,
+        reason: String,
+    },
+    InitCreateTranslationsFile {
+        path: PathBuf,
+        reason: String,
+    },
+    InitWriteTranslationsExample {
+        path: PathBuf,
+        reason: String,
+    },
+    ParseTranslationFileCannotOpen {
+        lang: String,
+        path: PathBuf,
+        reason: String,
+    },
+    ParseTranslationFileInvalidJson {
+        lang: String,
+        path: PathBuf,
+        error: Box<InvalidTranslations>,
+    },
+    ParseTranslationFileRepeatedLanguageKey {
+        lang: String,
+    },
+    /// Every problem found while loading the translation files.
+    LoadTranslationFiles(Vec<TrustlateError>),
+    FixTreeCannotOpenSourceFile {
+        lang: String,
+        path: PathBuf,
+        reason: String,
+    },
+    FixTreeCannotWriteToSourceFile {
+        lang: String,
+        path: PathBuf,
+        reason: String,
+    },
+    GenerateCannotCreateOutputFile {
+        path: PathBuf,
+        reason: String,
+    },
+    GenerateCannotCreateOutputFolders {
+        path: PathBuf,
+        reason: String,
+    },
+    GenerateCannotWriteToOutputFile {
+        path: PathBuf,
+        reason: String,
+    },
+    GenerateCannotGenerateCode {
+        lang: String,
+    },
+    /// Two keys of the same level that the generated code would name the same way.
+    GenerateIdentifierCollision {
+        first: TreePath,
+        second: TreePath,
+    },
+    ExportCannotCreateOutputFolders {
+        path: PathBuf,
+        reason: String,
+    },
+    ExportCannotWriteToOutputFile {
+        path: PathBuf,
+        reason: String,
+    },
+    ImportCannotOpenFile {
+        lang: String,
+        path: PathBuf,
+        reason: String,
+    },
+    ImportInvalidFile {
+        lang: String,
+        path: PathBuf,
+        error: Box<InvalidTranslations>,
+    },
+}
+
+impl TrustlateError {
+    /// The exit code trustlate finishes with because of this error, following the categories of
+    /// `sysexits.h`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // EX_CONFIG
+            Self::OpenConfigFile { .. }
+            | Self::ParseConfigFile { .. }
+            | Self::ParseTranslationFileRepeatedLanguageKey { .. } => 78,
+            // EX_NOINPUT
+            Self::ParseTranslationFileCannotOpen { .. } | Self::ImportCannotOpenFile { .. } => 66,
+            // EX_DATAERR
+            Self::ParseTranslationFileInvalidJson { .. }
+            | Self::GenerateIdentifierCollision { .. }
+            | Self::ImportInvalidFile { .. } => 65,
+            // EX_IOERR
+            Self::FixTreeCannotOpenSourceFile { .. }
+            | Self::FixTreeCannotWriteToSourceFile { .. }
+            | Self::GenerateCannotWriteToOutputFile { .. }
+            | Self::ExportCannotWriteToOutputFile { .. }
+            | Self::InitWriteConfigFile { .. }
+            | Self::InitWriteTranslationsExample { .. } => 74,
+            // EX_CANTCREAT
+            Self::InitCreateConfigFile { .. }
+            | Self::InitCreateSourceDir { .. }
+            | Self::InitCreateTargetDir { .. }
+            | Self::InitCreateTranslationsFile { .. }
+            | Self::GenerateCannotCreateOutputFile { .. }
+            | Self::GenerateCannotCreateOutputFolders { .. }
+            | Self::ExportCannotCreateOutputFolders { .. } => 73,
+            // EX_SOFTWARE
+            Self::GenerateCannotGenerateCode { .. } => 70,
+            // The most severe problem decides, configuration problems being the most severe ones.
+            Self::LoadTranslationFiles(errors) => errors
+                .iter()
+                .map(|error| error.exit_code())
+                .max()
+                .unwrap_or(65),
+        }
+    }
+
+    /// Renders the error as a diagnostic for the terminal, pointing at the offending line of the
+    /// file when it is known.
+    pub fn report(&self) -> String {
+        if let Self::LoadTranslationFiles(errors) = self {
+            let reports: Vec<String> = errors.iter().map(|error| error.report()).collect();
+            return format!(
+                "{}\n\n{}: {}",
+                reports.join("\n\n"),
+                "error".bold().red(),
+                self.to_string().bold()
+            );
+        }
+
+        self.report_as("error".bold().red())
+    }
+
+    /// Renders the error as a warning, for the problems trustlate skips over instead of stopping at
+    /// them, such as the translations `import` rejects.
+    pub fn warning_report(&self) -> String {
+        self.report_as("warning".bold().yellow())
+    }
+
+    fn report_as(&self, label: ColoredString) -> String {
+        let mut report = format!("{}: {}", label, self.to_string().bold());
+
+        let (path, tree_path, span) = match self {
+            Self::ParseConfigFile { path, span, .. } => (path, None, span.as_ref()),
+            Self::ParseTranslationFileInvalidJson { path, error, .. }
+            | Self::ImportInvalidFile { path, error, .. } => {
+                (path, error.tree_path.as_ref(), error.span.as_ref())
+            }
+            _ => return report,
+        };
+
+        let gutter = span.map(|s| s.line.to_string().len()).unwrap_or(1);
+        let pad = " ".repeat(gutter);
+        match span {
+            Some(span) => {
+                report += &format!(
+                    "\n{pad}{} {}:{}:{}",
+                    "-->".bold().blue(),
+                    path.display(),
+                    span.line,
+                    span.column
+                );
+                let marker = format!("{}^", " ".repeat(span.column - 1));
+                report += &format!("\n{pad} {}", "|".bold().blue());
+                report += &format!(
+                    "\n{} {} {}",
+                    span.line.to_string().bold().blue(),
+                    "|".bold().blue(),
+                    span.text
+                );
+                report += &format!("\n{pad} {} {}", "|".bold().blue(), marker.bold().red());
+            }
+            None => {
+                report += &format!("\n{pad}{} {}", "-->".bold().blue(), path.display());
+            }
+        }
+        if let Some(tree_path) = tree_path {
+            report += &format!("\n{pad} {} key: {}", "=".bold().blue(), tree_path);
+        }
+        report
+    }
+}
+
+impl Display for TrustlateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenConfigFile { path, reason } => write!(
+                f,
+                "cannot open the configuration file {}: {reason}",
+                path.display()
+            ),
+            Self::ParseConfigFile { path, reason, .. } => {
+                write!(f, "invalid configuration file {}: {reason}", path.display())
+            }
+            Self::InitCreateConfigFile { path, reason } => write!(
+                f,
+                "cannot create the configuration file {}: {reason}",
+                path.display()
+            ),
+            Self::InitWriteConfigFile { path, reason } => write!(
+                f,
+                "cannot write the configuration file {}: {reason}",
+                path.display()
+            ),
+            Self::InitCreateSourceDir { path, reason } => write!(
+                f,
+                "cannot create the translations directory {}: {reason}",
+                path.display()
+            ),
+            Self::InitCreateTargetDir { path, reason } => write!(
+                f,
+                "cannot create the generated code directory {}: {reason}",
+                path.display()
+            ),
+            Self::InitCreateTranslationsFile { path, reason } => write!(
+                f,
+                "cannot create the translations file {}: {reason}",
+                path.display()
+            ),
+            Self::InitWriteTranslationsExample { path, reason } => write!(
+                f,
+                "cannot write the example translations to {}: {reason}",
+                path.display()
+            ),
+            Self::ParseTranslationFileCannotOpen { lang, path, reason } => write!(
+                f,
+                "cannot open the translations file of {} ({}): {reason}",
+                lang.to_uppercase(),
+                path.display()
+            ),
+            Self::ParseTranslationFileInvalidJson { lang, path, error } => write!(
+                f,
+                "invalid translations file of {} ({}): {}",
+                lang.to_uppercase(),
+                path.display(),
+                error.reason
+            ),
+            Self::ParseTranslationFileRepeatedLanguageKey { lang } => write!(
+                f,
+                "the language {} is configured more than once",
+                lang.to_uppercase()
+            ),
+            Self::LoadTranslationFiles(errors) => write!(
+                f,
+                "found {} problems in the translation files",
+                errors.len()
+            ),
+            Self::FixTreeCannotOpenSourceFile { lang, path, reason } => write!(
+                f,
+                "cannot open the translations file of {} ({}) to update it: {reason}",
+                lang.to_uppercase(),
+                path.display()
+            ),
+            Self::FixTreeCannotWriteToSourceFile { lang, path, reason } => write!(
+                f,
+                "cannot write the updated translations of {} to {}: {reason}",
+                lang.to_uppercase(),
+                path.display()
+            ),
+            Self::GenerateCannotCreateOutputFile { path, reason } => write!(
+                f,
+                "cannot create the output file {}: {reason}",
+                path.display()
+            ),
+            Self::GenerateCannotCreateOutputFolders { path, reason } => write!(
+                f,
+                "cannot create the output directory {}: {reason}",
+                path.display()
+            ),
+            Self::GenerateCannotWriteToOutputFile { path, reason } => write!(
+                f,
+                "cannot write the output file {}: {reason}",
+                path.display()
+            ),
+            Self::GenerateCannotGenerateCode { lang } => write!(
+                f,
+                "cannot generate the code for the translations of {}",
+                lang.to_uppercase()
+            ),
+            Self::GenerateIdentifierCollision { first, second } => write!(
+                f,
+                "the keys {} and {} turn into the same identifier in the generated code, rename one of them",
+                first, second
+            ),
+            Self::ExportCannotCreateOutputFolders { path, reason } => write!(
+                f,
+                "cannot create the export directory {}: {reason}",
+                path.display()
+            ),
+            Self::ExportCannotWriteToOutputFile { path, reason } => write!(
+                f,
+                "cannot write the exported file {}: {reason}",
+                path.display()
+            ),
+            Self::ImportCannotOpenFile { lang, path, reason } => write!(
+                f,
+                "cannot open the file to import the translations of {} from ({}): {reason}",
+                lang.to_uppercase(),
+                path.display()
+            ),
+            Self::ImportInvalidFile { lang, path, error } => write!(
+                f,
+                "invalid imported translations of {} ({}): {}",
+                lang.to_uppercase(),
+                path.display(),
+                error.reason
+            ),
+        }
+    }
 }
@@ -0,0 +1,169 @@
+use crate::trustlate::{
+    codegen::{escape_keyword, form_segments, to_snake_case, FormSegment, JAVA_KEYWORDS},
+    exports::{argument_position, export_leaves, ExportFile},
+    locales::base_language,
+    translations_tree::{LeafType, Parameter, TranslationsTree, TreePath},
+};
+
+/// Exports the translations as Android string resources.
+///
+/// Every language is written to `values-<qualifier>/strings.xml`, and the base language, which
+/// comes first, to `values/strings.xml` too, so that Android falls back to it for the locales
+/// without translations.
+pub fn export_android(tree: &[(&String, TranslationsTree)]) -> Vec<ExportFile> {
+    let base_tree = &tree[0].1;
+    let mut files = vec![];
+    for (idx, (lang, translations)) in tree.iter().enumerate() {
+        let content = generate_strings_xml(translations, base_tree);
+        if idx == 0 {
+            files.push(ExportFile {
+                path: "values/strings.xml".to_string(),
+                content: content.clone(),
+            });
+        }
+        files.push(ExportFile {
+            path: format!("values-{}/strings.xml", resource_qualifier(lang)),
+            content,
+        });
+    }
+    files
+}
+
+/// Returns the qualifier of the resource directories of a language, e.g. `pt-rBR` for `pt-BR` or
+/// `b+zh+Hant` for `zh-Hant`. Languages are normalized as in the Apple exports, so that `kr` is
+/// written to `values-ko`.
+fn resource_qualifier(lang: &str) -> String {
+    let mut subtags: Vec<String> = lang.split(['-', '_']).map(str::to_string).collect();
+    subtags[0] = base_language(&subtags[0]);
+    match subtags.as_slice() {
+        [language] => language.clone(),
+        [language, region] if region.len() == 2 => {
+            format!("{}-r{}", language, region.to_uppercase())
+        }
+        _ => format!("b+{}", subtags.join("+")),
+    }
+}
+
+fn generate_strings_xml(tree: &TranslationsTree, base_tree: &TranslationsTree) -> String {
+    let resources = export_leaves(tree, base_tree)
+        .iter()
+        .map(|export| make_resource(&resource_name(&export.path), export.leaf, export.arguments))
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!-- Generated by trustlate. DO NOT EDIT. -->\n<resources>\n{}</resources>\n",
+        resources
+    )
+}
+
+/// Flattens the path of a translation into a resource name, e.g. `main_page_title` for
+/// `mainPage -> title`.
+fn resource_name(path: &TreePath) -> String {
+    let name = path
+        .steps()
+        .iter()
+        .map(|step| to_snake_case(step))
+        .collect::<Vec<String>>()
+        .join("_");
+    // Resource names become fields of the `R` classes.
+    escape_keyword(name, JAVA_KEYWORDS)
+}
+
+/// Renders a translation as string resources, `arguments` being the parameters in the order they
+/// are passed as format arguments.
+fn make_resource(name: &str, leaf: &LeafType, arguments: &[Parameter]) -> String {
+    match leaf {
+        LeafType::LiteralLeaf(text) => {
+            format!(
+                "    <string name=\"{}\">{}</string>\n",
+                name,
+                android_string(text, arguments, None)
+            )
+        }
+        LeafType::ParametrizedLeaf { raw, .. } => format!(
+            "    <string name=\"{}\">{}</string>\n",
+            name,
+            android_string(raw, arguments, None)
+        ),
+        // Android picks the quantity, but the count still has to be passed as the first argument
+        // to be shown.
+        LeafType::PluralLeaf {
+            parameter, forms, ..
+        } => {
+            let items = forms
+                .iter()
+                .map(|(category, form)| {
+                    format!(
+                        "        <item quantity=\"{}\">{}</item>\n",
+                        category,
+                        android_string(form, arguments, Some(parameter))
+                    )
+                })
+                .collect::<String>();
+            format!("    <plurals name=\"{}\">\n{}    </plurals>\n", name, items)
+        }
+        // Android has no selects, so every case becomes a string of its own named after it.
+        LeafType::SelectLeaf { cases, .. } => cases
+            .iter()
+            .map(|(case, text)| {
+                format!(
+                    "    <string name=\"{}_{}\">{}</string>\n",
+                    name,
+                    to_snake_case(case),
+                    android_string(text, arguments, None)
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Turns a translation into the text of a string resource, with its `{{...}}` placeholders
+/// converted into positional format arguments, the `count` of a plural being an integer.
+fn android_string(text: &str, arguments: &[Parameter], count: Option<&str>) -> String {
+    let formatted = count.is_some() || !arguments.is_empty();
+    let result = form_segments(text)
+        .iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) => escape_android(text, formatted),
+            FormSegment::Placeholder(name) if Some(name.as_str()) == count => "%1$d".to_string(),
+            FormSegment::Placeholder(name) => {
+                format!("%{}$s", argument_position(name, arguments, count))
+            }
+        })
+        .collect::<String>();
+
+    // Android trims the spaces around a string and collapses the repeated ones, unless escaped.
+    let chars: Vec<char> = result.chars().collect();
+    let mut result = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if c == ' ' && (idx == 0 || idx == chars.len() - 1 || chars[idx - 1] == ' ') {
+            result += "\\u0020";
+        } else {
+            result.push(c);
+        }
+    }
+    if result.starts_with('@') || result.starts_with('?') {
+        result.insert(0, '\\');
+    }
+    result
+}
+
+/// Escapes a text following the rules of Android string resources, `formatted` being whether the
+/// string takes format arguments, which makes `%` special.
+fn escape_android(text: &str, formatted: bool) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped += "\\\\",
+            '\'' => escaped += "\\'",
+            '"' => escaped += "\\\"",
+            '\n' => escaped += "\\n",
+            '\t' => escaped += "\\t",
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '%' if formatted => escaped += "%%",
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,196 @@
+use serde_json::{json, Map, Value};
+
+use crate::trustlate::{
+    codegen::{form_segments, FormSegment},
+    exports::{argument_position, export_leaves, key_path, ExportFile, ExportLeaf},
+    locales::base_language,
+    reports::escape_xml,
+    translations_tree::{LeafType, Parameter, TranslationsTree},
+};
+
+/// Exports the translations as `<lang>.lproj/Localizable.strings` files, with the plurals of every
+/// language in a `Localizable.stringsdict` next to them, since `.strings` files cannot hold them.
+pub fn export_strings(tree: &[(&String, TranslationsTree)]) -> Vec<ExportFile> {
+    let base_tree = &tree[0].1;
+    let mut files = vec![];
+    for (lang, translations) in tree {
+        let lproj = format!("{}.lproj", apple_language(lang));
+        let mut strings = String::new();
+        let mut plurals = String::new();
+        for export in export_leaves(translations, base_tree) {
+            match export.leaf {
+                LeafType::PluralLeaf { .. } => plurals += &make_stringsdict_entry(&export),
+                _ => {
+                    for (key, text) in flat_entries(&export) {
+                        strings += &format!(
+                            "\"{}\" = \"{}\";\n",
+                            escape_strings(&key),
+                            escape_strings(&text)
+                        );
+                    }
+                }
+            }
+        }
+
+        files.push(ExportFile {
+            path: format!("{}/Localizable.strings", lproj),
+            content: format!("/* Generated by trustlate. DO NOT EDIT. */\n\n{}", strings),
+        });
+        if !plurals.is_empty() {
+            files.push(ExportFile {
+                path: format!("{}/Localizable.stringsdict", lproj),
+                content: format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<!-- Generated by trustlate. DO NOT EDIT. -->\n<plist version=\"1.0\">\n<dict>\n{}</dict>\n</plist>\n",
+                    plurals
+                ),
+            });
+        }
+    }
+    files
+}
+
+/// Exports the translations of every language as a single `Localizable.xcstrings` string catalog,
+/// with the base language as its source language.
+pub fn export_xcstrings(tree: &[(&String, TranslationsTree)]) -> Vec<ExportFile> {
+    let base_tree = &tree[0].1;
+    let mut strings = Map::new();
+    for (lang, translations) in tree {
+        let lang = apple_language(lang);
+        for export in export_leaves(translations, base_tree) {
+            let units = match export.leaf {
+                LeafType::PluralLeaf {
+                    parameter, forms, ..
+                } => {
+                    let variations: Map<String, Value> = forms
+                        .iter()
+                        .map(|(category, form)| {
+                            let value = apple_string(form, export.arguments, Some(parameter));
+                            (category.to_string(), string_unit(&value))
+                        })
+                        .collect();
+                    vec![(
+                        key_path(&export.path),
+                        json!({ "variations": { "plural": variations } }),
+                    )]
+                }
+                _ => flat_entries(&export)
+                    .into_iter()
+                    .map(|(key, value)| (key, string_unit(&value)))
+                    .collect(),
+            };
+            for (key, unit) in units {
+                let entry = strings
+                    .entry(key)
+                    .or_insert_with(|| json!({ "extractionState": "manual", "localizations": {} }));
+                entry["localizations"][&lang] = unit;
+            }
+        }
+    }
+
+    let catalog = json!({
+        "sourceLanguage": apple_language(tree[0].0),
+        "strings": strings,
+        "version": "1.0",
+    });
+    vec![ExportFile {
+        path: "Localizable.xcstrings".to_string(),
+        content: format!("{}\n", serde_json::to_string_pretty(&catalog).unwrap()),
+    }]
+}
+
+/// Returns the language identifier Apple names the `.lproj` directories after, e.g. `pt-BR` or
+/// `zh-Hant`.
+fn apple_language(lang: &str) -> String {
+    let mut subtags = lang.split(['-', '_']);
+    let language = base_language(subtags.next().unwrap_or_default());
+    subtags.fold(language, |acc, subtag| {
+        let subtag = match subtag.len() {
+            // Scripts are written in title case.
+            4 => subtag[..1].to_uppercase() + &subtag[1..].to_lowercase(),
+            _ => subtag.to_uppercase(),
+        };
+        format!("{}-{}", acc, subtag)
+    })
+}
+
+/// Returns the keys and texts of a translation other than a plural. Apple has no selects, so every
+/// case of a select becomes a string of its own, keyed after it (e.g. `cart.invited.female`).
+fn flat_entries(export: &ExportLeaf) -> Vec<(String, String)> {
+    let key = key_path(&export.path);
+    match export.leaf {
+        LeafType::SelectLeaf { cases, .. } => cases
+            .iter()
+            .map(|(case, text)| {
+                (
+                    format!("{}.{}", key, case),
+                    apple_string(text, export.arguments, None),
+                )
+            })
+            .collect(),
+        leaf => vec![(key, apple_string(leaf.raw(), export.arguments, None))],
+    }
+}
+
+fn string_unit(value: &str) -> Value {
+    json!({ "stringUnit": { "state": "translated", "value": value } })
+}
+
+/// Renders a plural as an entry of a `.stringsdict`, whose format is the count variable itself.
+fn make_stringsdict_entry(export: &ExportLeaf) -> String {
+    let LeafType::PluralLeaf {
+        parameter, forms, ..
+    } = export.leaf
+    else {
+        return String::new();
+    };
+    let rules = forms
+        .iter()
+        .map(|(category, form)| {
+            format!(
+                "\t\t\t<key>{}</key>\n\t\t\t<string>{}</string>\n",
+                category,
+                escape_xml(&apple_string(form, export.arguments, Some(parameter)))
+            )
+        })
+        .collect::<String>();
+    format!(
+        "\t<key>{}</key>\n\t<dict>\n\t\t<key>NSStringLocalizedFormatKey</key>\n\t\t<string>%1$#@{}@</string>\n\t\t<key>{}</key>\n\t\t<dict>\n\t\t\t<key>NSStringFormatSpecTypeKey</key>\n\t\t\t<string>NSStringPluralRuleType</string>\n\t\t\t<key>NSStringFormatValueTypeKey</key>\n\t\t\t<string>lld</string>\n{}\t\t</dict>\n\t</dict>\n",
+        escape_xml(&key_path(&export.path)),
+        parameter,
+        parameter,
+        rules
+    )
+}
+
+/// Turns a translation into a format string, with its `{{...}}` placeholders converted into
+/// positional `%@` arguments, the `count` of a plural being an integer.
+fn apple_string(text: &str, arguments: &[Parameter], count: Option<&str>) -> String {
+    let formatted = count.is_some() || !arguments.is_empty();
+    form_segments(text)
+        .iter()
+        .map(|segment| match segment {
+            FormSegment::Text(text) if formatted => text.replace('%', "%%"),
+            FormSegment::Text(text) => text.to_string(),
+            FormSegment::Placeholder(name) if Some(name.as_str()) == count => "%1$lld".to_string(),
+            FormSegment::Placeholder(name) => {
+                format!("%{}$@", argument_position(name, arguments, count))
+            }
+        })
+        .collect()
+}
+
+/// Escapes a text to be written between the quotes of a `.strings` file.
+fn escape_strings(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped += "\\\\",
+            '"' => escaped += "\\\"",
+            '\n' => escaped += "\\n",
+            '\t' => escaped += "\\t",
+            '\r' => escaped += "\\r",
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,49 @@
+use crate::trustlate::{
+    exports::{key_path, ExportFile},
+    translations_tree::{TranslationTreeNode, TranslationsTree},
+};
+
+/// Exports the translations as a `trustlate.csv` table with a row per translation, keyed by its
+/// dot-joined key path, and a column per language, the base language first.
+///
+/// Cells are left empty for the translations a language lacks or that still hold the `filling`
+/// text, and texts are written as they are in the translation files so they can be imported back.
+pub fn export_csv(tree: &[(&String, TranslationsTree)], filling: &str) -> Vec<ExportFile> {
+    let header = std::iter::once("key")
+        .chain(tree.iter().map(|(lang, _)| lang.as_str()))
+        .map(csv_field)
+        .collect::<Vec<String>>()
+        .join(",");
+    let mut content = format!("{}\r\n", header);
+
+    let base_tree = &tree[0].1;
+    for path in base_tree.leaf_paths() {
+        let cells = tree
+            .iter()
+            .map(|(_, translations)| match translations.find_node_at(&path) {
+                Some(TranslationTreeNode::Leaf(leaf)) if !leaf.raw().contains(filling) => {
+                    csv_field(leaf.raw())
+                }
+                _ => String::new(),
+            });
+        let row = std::iter::once(csv_field(&key_path(&path)))
+            .chain(cells)
+            .collect::<Vec<String>>()
+            .join(",");
+        content += &format!("{}\r\n", row);
+    }
+
+    vec![ExportFile {
+        path: "trustlate.csv".to_string(),
+        content,
+    }]
+}
+
+/// Quotes a field when it holds a separator, a quote or a line break, as RFC 4180 does.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,164 @@
+pub mod android;
+pub mod apple;
+pub mod csv;
+pub mod po;
+pub mod xliff;
+
+use std::{collections::HashMap, fs, path::Path};
+
+use android::export_android;
+use apple::{export_strings, export_xcstrings};
+use csv::export_csv;
+use po::export_po;
+use xliff::{export_xliff, XliffVersion};
+
+use super::{
+    config::Config,
+    errors::TrustlateError,
+    translations_tree::{LeafType, Parameter, TranslationTreeNode, TranslationsTree, TreePath},
+};
+
+/// The resource formats `export` can write the translations in.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// A `values-<lang>/strings.xml` Android resource file per language
+    Android,
+    /// A `<lang>.lproj/Localizable.strings` file per language, with a `Localizable.stringsdict`
+    /// for the plurals
+    Strings,
+    /// A `Localizable.xcstrings` string catalog with every language
+    Xcstrings,
+    /// A gettext `.pot` template with the base language and a `.po` catalog per target language
+    Po,
+    /// An XLIFF 1.2 document per target language
+    Xliff,
+    /// An XLIFF 2.0 document per target language
+    Xliff2,
+    /// A `trustlate.csv` table with a row per translation and a column per language
+    Csv,
+}
+
+/// A file to export, with its path relative to the output directory.
+pub struct ExportFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// A translation to export, together with the parameters of the base language one, which decide
+/// the position of its format arguments so that they are the same in every language.
+pub struct ExportLeaf<'a> {
+    pub path: TreePath,
+    pub leaf: &'a LeafType,
+    pub arguments: &'a [Parameter],
+}
+
+/// Returns every translation of a tree, in document order, along with the parameters of the
+/// translation the base language has at the same path.
+pub fn export_leaves<'a>(
+    tree: &'a TranslationsTree,
+    base_tree: &'a TranslationsTree,
+) -> Vec<ExportLeaf<'a>> {
+    fn collect<'a>(
+        path: TreePath,
+        node: &'a TranslationTreeNode,
+        base: Option<&'a TranslationTreeNode>,
+        acc: &mut Vec<ExportLeaf<'a>>,
+    ) {
+        match (node, base) {
+            (TranslationTreeNode::NonLeaf(children), base) => {
+                for (key, child) in children {
+                    let base = match base {
+                        Some(TranslationTreeNode::NonLeaf(base_children)) => {
+                            base_children.get(key).map(|base| base.as_ref())
+                        }
+                        _ => None,
+                    };
+                    collect(path.walk(key), child, base, acc);
+                }
+            }
+            (TranslationTreeNode::Leaf(leaf), base) => {
+                let arguments = match base {
+                    Some(TranslationTreeNode::Leaf(base_leaf)) => base_leaf.parameters(),
+                    _ => leaf.parameters(),
+                };
+                acc.push(ExportLeaf {
+                    path,
+                    leaf,
+                    arguments,
+                });
+            }
+        }
+    }
+
+    let mut leaves = vec![];
+    for (key, node) in &tree.children {
+        let base = base_tree.children.get(key).map(|base| base.as_ref());
+        collect(TreePath::new().walk(key), node, base, &mut leaves);
+    }
+    leaves
+}
+
+/// The dot-joined path of a translation (e.g. `mainPage.title`), which is how the formats without
+/// nesting key the translations.
+pub fn key_path(path: &TreePath) -> String {
+    path.steps().join(".")
+}
+
+/// Returns the position, starting at 1, of the format argument of a placeholder: the `count` of a
+/// plural comes first, followed by the `arguments` in order and by the selector of a select, if
+/// shown.
+pub fn argument_position(name: &str, arguments: &[Parameter], count: Option<&str>) -> usize {
+    if Some(name) == count {
+        return 1;
+    }
+    let offset = usize::from(count.is_some());
+    let position = arguments
+        .iter()
+        .position(|param| param.name == name)
+        .unwrap_or(arguments.len());
+    position + offset + 1
+}
+
+/// Writes the translations of every language in the given format to `output_dir`.
+pub fn export(
+    config: &Config,
+    trees: &HashMap<String, TranslationsTree>,
+    format: ExportFormat,
+    output_dir: &Path,
+) -> Result<(), TrustlateError> {
+    // As with the generated code, the exported files follow the order of the base language.
+    let base_tree = trees.get(&config.base_lang).unwrap();
+    let tree: Vec<(&String, TranslationsTree)> = config
+        .langs()
+        .map(|lang| (lang, trees.get(lang).unwrap().ordered_as(base_tree)))
+        .collect();
+
+    let files = match format {
+        ExportFormat::Android => export_android(&tree),
+        ExportFormat::Strings => export_strings(&tree),
+        ExportFormat::Xcstrings => export_xcstrings(&tree),
+        ExportFormat::Po => export_po(&tree, config.filling()),
+        ExportFormat::Xliff => export_xliff(&tree, config.filling(), XliffVersion::V1),
+        ExportFormat::Xliff2 => export_xliff(&tree, config.filling(), XliffVersion::V2),
+        ExportFormat::Csv => export_csv(&tree, config.filling()),
+    };
+
+    for file in files {
+        let filepath = output_dir.join(&file.path);
+        let folder = filepath.parent().unwrap();
+        fs::create_dir_all(folder).map_err(|err| {
+            TrustlateError::ExportCannotCreateOutputFolders {
+                path: folder.to_path_buf(),
+                reason: err.to_string(),
+            }
+        })?;
+        fs::write(&filepath, file.content).map_err(|err| {
+            TrustlateError::ExportCannotWriteToOutputFile {
+                path: filepath.clone(),
+                reason: err.to_string(),
+            }
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::trustlate::{
+    exports::{key_path, ExportFile},
+    locales::language_tag,
+    translations_tree::{TranslationTreeNode, TranslationsTree},
+};
+
+/// Exports the translations as GNU gettext catalogs: a `trustlate.pot` template with the base
+/// language translations and a `<lang>.po` file per target language.
+///
+/// Every translation is an entry whose context is its key path, whose `msgid` is the base language
+/// text and whose `msgstr` is the target language one, left empty while it holds the `filling`
+/// text so that translation tools show it as untranslated. Texts are written as they are in the
+/// translation files, placeholders, plurals and selects included, so they can be imported back.
+pub fn export_po(tree: &[(&String, TranslationsTree)], filling: &str) -> Vec<ExportFile> {
+    let base_tree = &tree[0].1;
+    let mut files = vec![ExportFile {
+        path: "trustlate.pot".to_string(),
+        content: generate_catalog(base_tree, None, filling),
+    }];
+    for (lang, translations) in tree.iter().skip(1) {
+        files.push(ExportFile {
+            path: format!("{}.po", lang),
+            content: generate_catalog(base_tree, Some((lang, translations)), filling),
+        });
+    }
+    files
+}
+
+/// Generates the catalog of a target language, or the template when there is none.
+fn generate_catalog(
+    base_tree: &TranslationsTree,
+    target: Option<(&str, &TranslationsTree)>,
+    filling: &str,
+) -> String {
+    let language = target
+        .map(|(lang, _)| language_tag(lang).replace('-', "_"))
+        .unwrap_or_default();
+    let mut catalog = format!(
+        "# Translations exported by trustlate.\nmsgid \"\"\nmsgstr \"\"\n\"Project-Id-Version: trustlate\\n\"\n\"Language: {}\\n\"\n\"MIME-Version: 1.0\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\"Content-Transfer-Encoding: 8bit\\n\"\n",
+        language
+    );
+
+    for path in base_tree.leaf_paths() {
+        let Some(TranslationTreeNode::Leaf(base_leaf)) = base_tree.find_node_at(&path) else {
+            continue;
+        };
+        let translation = match target.and_then(|(_, tree)| tree.find_node_at(&path)) {
+            Some(TranslationTreeNode::Leaf(leaf)) if !leaf.raw().contains(filling) => leaf.raw(),
+            _ => "",
+        };
+        catalog += &format!(
+            "\nmsgctxt \"{}\"\nmsgid \"{}\"\nmsgstr \"{}\"\n",
+            escape_po(&key_path(&path)),
+            escape_po(base_leaf.raw()),
+            escape_po(translation)
+        );
+    }
+    catalog
+}
+
+/// Escapes a text to be written between the quotes of a catalog.
+fn escape_po(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped += "\\\\",
+            '"' => escaped += "\\\"",
+            '\n' => escaped += "\\n",
+            '\t' => escaped += "\\t",
+            '\r' => escaped += "\\r",
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
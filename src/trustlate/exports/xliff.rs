@@ -0,0 +1,162 @@
+use crate::trustlate::{
+    exports::{key_path, ExportFile},
+    locales::language_tag,
+    reports::escape_xml,
+    translations_tree::{TranslationTreeNode, TranslationsTree},
+};
+
+/// The versions of XLIFF trustlate can export to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XliffVersion {
+    V1,
+    V2,
+}
+
+/// Exports a `<lang>.xlf` XLIFF document per target language, with a unit per translation keyed by
+/// its dot-joined key path.
+///
+/// Sources are the base language texts and targets the target language ones, left out while they
+/// hold the `filling` text. Placeholders become `<ph>` elements, so translation tools protect
+/// them, while the rest of the texts, plurals and selects included, are kept as they are written in
+/// the translation files.
+pub fn export_xliff(
+    tree: &[(&String, TranslationsTree)],
+    filling: &str,
+    version: XliffVersion,
+) -> Vec<ExportFile> {
+    let (base_lang, base_tree) = &tree[0];
+    tree.iter()
+        .skip(1)
+        .map(|(lang, translations)| {
+            let mut units = String::new();
+            for path in base_tree.leaf_paths() {
+                let Some(TranslationTreeNode::Leaf(base_leaf)) = base_tree.find_node_at(&path)
+                else {
+                    continue;
+                };
+                let target = match translations.find_node_at(&path) {
+                    Some(TranslationTreeNode::Leaf(leaf)) if !leaf.raw().contains(filling) => {
+                        Some(xliff_content(leaf.raw(), base_leaf.raw(), version))
+                    }
+                    _ => None,
+                };
+                let id = escape_xml(&key_path(&path));
+                let note = escape_xml(&path.to_string());
+                let source = xliff_content(base_leaf.raw(), base_leaf.raw(), version);
+                units += &match (version, target) {
+                    (XliffVersion::V1, Some(target)) => format!(
+                        "      <trans-unit id=\"{id}\" xml:space=\"preserve\">\n        <source>{source}</source>\n        <target state=\"translated\">{target}</target>\n        <note>{note}</note>\n      </trans-unit>\n"
+                    ),
+                    (XliffVersion::V1, None) => format!(
+                        "      <trans-unit id=\"{id}\" xml:space=\"preserve\">\n        <source>{source}</source>\n        <note>{note}</note>\n      </trans-unit>\n"
+                    ),
+                    (XliffVersion::V2, Some(target)) => format!(
+                        "    <unit id=\"{id}\" xml:space=\"preserve\">\n      <notes>\n        <note category=\"location\">{note}</note>\n      </notes>\n      <segment state=\"translated\">\n        <source>{source}</source>\n        <target>{target}</target>\n      </segment>\n    </unit>\n"
+                    ),
+                    (XliffVersion::V2, None) => format!(
+                        "    <unit id=\"{id}\" xml:space=\"preserve\">\n      <notes>\n        <note category=\"location\">{note}</note>\n      </notes>\n      <segment state=\"initial\">\n        <source>{source}</source>\n      </segment>\n    </unit>\n"
+                    ),
+                };
+            }
+
+            let (source_language, target_language) = (language_tag(base_lang), language_tag(lang));
+            let content = match version {
+                XliffVersion::V1 => format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n  <file original=\"trustlate\" datatype=\"plaintext\" source-language=\"{}\" target-language=\"{}\">\n    <body>\n{}    </body>\n  </file>\n</xliff>\n",
+                    source_language, target_language, units
+                ),
+                XliffVersion::V2 => format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff version=\"2.0\" xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" srcLang=\"{}\" trgLang=\"{}\">\n  <file id=\"trustlate\">\n{}  </file>\n</xliff>\n",
+                    source_language, target_language, units
+                ),
+            };
+            ExportFile {
+                path: format!("{}.xlf", lang),
+                content,
+            }
+        })
+        .collect()
+}
+
+/// Escapes a text to be the content of a source or target, turning its `{{name}}` and
+/// `{{name:type}}` placeholders into `<ph>` elements identified by their name.
+///
+/// Ids are unique within the `source` text of the unit, the repeated placeholders being numbered,
+/// and the target reuses them: the placeholders it repeats more often than the source does take
+/// the id of the last one of the source. Plurals and selects are left as text, only the
+/// placeholders inside of their forms are marked.
+fn xliff_content(text: &str, source: &str, version: XliffVersion) -> String {
+    let re = regex::Regex::new(r"\{\{\s*(\w+)\s*(:\s*\w+\s*)?\}\}").unwrap();
+    let source_names: Vec<&str> = re
+        .captures_iter(source)
+        .map(|caps| caps.get(1).unwrap().as_str())
+        .collect();
+    let mut content = String::new();
+    let mut seen: Vec<&str> = vec![];
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let placeholder = caps.get(0).unwrap();
+        let name = caps.get(1).unwrap().as_str();
+        seen.push(name);
+        let occurrence = seen.iter().filter(|seen_name| **seen_name == name).count();
+        let in_source = source_names
+            .iter()
+            .filter(|source_name| **source_name == name)
+            .count();
+        let id = match occurrence.min(in_source) {
+            0 | 1 => name.to_string(),
+            occurrence => format!("{}_{}", name, occurrence),
+        };
+        content += &escape_xml(&text[last..placeholder.start()]);
+        content += &match version {
+            XliffVersion::V1 => format!(
+                "<ph id=\"{}\">{}</ph>",
+                id,
+                escape_xml(placeholder.as_str())
+            ),
+            XliffVersion::V2 => format!(
+                "<ph id=\"{}\" equiv=\"{}\" disp=\"{}\"/>",
+                id,
+                escape_xml(placeholder.as_str()),
+                name
+            ),
+        };
+        last = placeholder.end();
+    }
+    content + &escape_xml(&text[last..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_placeholders_are_numbered_in_the_source() {
+        assert_eq!(
+            xliff_content("{{n}} of {{n}}", "{{n}} of {{n}}", XliffVersion::V1),
+            r#"<ph id="n">{{n}}</ph> of <ph id="n_2">{{n}}</ph>"#
+        );
+    }
+
+    #[test]
+    fn targets_take_the_ids_of_the_source() {
+        let source = "{{name}} has {{count:number}} messages";
+        assert_eq!(
+            xliff_content("{{count}} messages for {{name}}", source, XliffVersion::V2),
+            r#"<ph id="count" equiv="{{count}}" disp="count"/> messages for <ph id="name" equiv="{{name}}" disp="name"/>"#
+        );
+        // A placeholder repeated in the target takes the id of the last one of the source.
+        assert_eq!(
+            xliff_content(
+                "{{name}}, {{name}} & {{name}}",
+                "{{name}} or {{name}}",
+                XliffVersion::V1
+            ),
+            r#"<ph id="name">{{name}}</ph>, <ph id="name_2">{{name}}</ph> &amp; <ph id="name_2">{{name}}</ph>"#
+        );
+        assert_eq!(
+            xliff_content("{{name}}", "{{name}} or {{name}}", XliffVersion::V1),
+            r#"<ph id="name">{{name}}</ph>"#
+        );
+    }
+}
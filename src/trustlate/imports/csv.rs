@@ -0,0 +1,200 @@
+use crate::trustlate::{
+    errors::{InvalidTranslations, SourceSpan},
+    imports::ImportedTranslation,
+};
+
+/// Reads the translations of a language from the column named after it of a CSV table whose first
+/// column holds the key paths.
+///
+/// Both commas and semicolons are accepted as separators, the latter being what spreadsheets use
+/// in the locales that write decimals with commas. Returns `None` when the table has no column for
+/// the language.
+pub fn parse_csv(
+    source: &str,
+    lang: &str,
+) -> Result<Option<Vec<ImportedTranslation>>, InvalidTranslations> {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let first_line = source.lines().next().unwrap_or_default();
+    let separator = match first_line.contains(',') || !first_line.contains(';') {
+        true => ',',
+        false => ';',
+    };
+    let mut records = read_records(source, separator).map_err(|offset| InvalidTranslations {
+        reason: "Unclosed quoted field".to_string(),
+        tree_path: None,
+        span: SourceSpan::at_offset(source, offset),
+    })?;
+    if records.is_empty() {
+        return Ok(None);
+    }
+
+    let header = records.remove(0);
+    let Some(column) = header.iter().position(|(name, _)| name.trim() == lang) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        records
+            .into_iter()
+            .filter_map(|mut record| {
+                let (key, _) = record.first()?.clone();
+                if column >= record.len() {
+                    return None;
+                }
+                let (text, offset) = record.swap_remove(column);
+                Some(ImportedTranslation {
+                    key: key.trim().to_string(),
+                    text,
+                    source: None,
+                    span: SourceSpan::at_offset(source, offset),
+                    rejection: None,
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Splits a CSV table into its records, made of the fields and the offsets they start at, as
+/// RFC 4180 describes it. Returns the offset of the quoted field left unclosed, if any.
+fn read_records(source: &str, separator: char) -> Result<Vec<Vec<(String, usize)>>, usize> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, _)) = chars.peek() {
+        let mut field = String::new();
+        if chars.next_if(|(_, c)| *c == '"').is_some() {
+            loop {
+                match chars.next() {
+                    Some((_, '"')) if chars.next_if(|(_, c)| *c == '"').is_some() => {
+                        field.push('"')
+                    }
+                    Some((_, '"')) => break,
+                    Some((_, c)) => field.push(c),
+                    None => return Err(start),
+                }
+            }
+        }
+        while let Some((_, c)) = chars.next_if(|(_, c)| *c != separator && *c != '\n') {
+            if c != '\r' {
+                field.push(c);
+            }
+        }
+        record.push((field, start));
+
+        if chars.next_if(|(_, c)| *c == separator).is_some() {
+            if chars.peek().is_none() {
+                record.push((String::new(), source.len()));
+            }
+            continue;
+        }
+        chars.next();
+        // Blank lines are not records.
+        if record.len() > 1 || !record[0].0.is_empty() {
+            records.push(std::mem::take(&mut record));
+        }
+        record.clear();
+    }
+    if !record.is_empty() {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::{exports::csv::export_csv, translations_tree::TranslationsTree};
+
+    fn texts(translations: &[ImportedTranslation]) -> Vec<(&str, &str)> {
+        translations
+            .iter()
+            .map(|translation| (translation.key.as_str(), translation.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn translations_are_read_from_the_column_of_the_language() {
+        let translations = parse_csv(
+            concat!(
+                "key,en,fr\r\n",
+                "cart.title,Cart,Panier\r\n",
+                "\r\n",
+                "quote,\"Say \"\"hi\"\", all\",\"Dis \"\"salut\"\",\r\ntous\"\r\n",
+                "short,Short\r\n",
+                "empty,Empty,\r\n",
+            ),
+            "fr",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            texts(&translations),
+            [
+                ("cart.title", "Panier"),
+                ("quote", "Dis \"salut\",\r\ntous"),
+                ("empty", ""),
+            ]
+        );
+        assert_eq!(translations[1].span.as_ref().unwrap().line, 4);
+    }
+
+    #[test]
+    fn semicolons_and_byte_order_marks_are_accepted() {
+        let translations = parse_csv("\u{feff}key;en;fr\nprice;1,5 €;1,5 €\n", "fr")
+            .unwrap()
+            .unwrap();
+        assert_eq!(texts(&translations), [("price", "1,5 €")]);
+    }
+
+    #[test]
+    fn tables_without_a_column_for_the_language_are_skipped() {
+        assert!(parse_csv("key,en\na,A\n", "fr").unwrap().is_none());
+        assert!(parse_csv("", "fr").unwrap().is_none());
+    }
+
+    #[test]
+    fn unclosed_quotes_are_reported() {
+        let error = parse_csv("key,fr\na,\"Un\nb,Bé\n", "fr").err().unwrap();
+        assert_eq!(error.reason, "Unclosed quoted field");
+        let span = error.span.unwrap();
+        assert_eq!((span.line, span.column), (2, 3));
+    }
+
+    #[test]
+    fn exported_tables_are_imported_back() {
+        let en = TranslationsTree::from_json(
+            r#"{"cart": {"title": "Cart, {{name}}", "items": "{{n, plural, one{One item} other{{{n}} items}}}"}, "quote": "Say \"hi\"\nall", "todo": "Todo"}"#,
+        )
+        .unwrap();
+        let fr = TranslationsTree::from_json(
+            r#"{"cart": {"title": "Panier, {{name}}", "items": "{{n, plural, one{Un article} other{{{n}} articles}}}"}, "quote": "Dis \"salut\"\r\ntous", "todo": "TODO"}"#,
+        )
+        .unwrap();
+        let (en_lang, fr_lang) = ("en".to_string(), "fr".to_string());
+        let files = export_csv(&[(&en_lang, en), (&fr_lang, fr)], "TODO");
+        let table = &files[0].content;
+        assert_eq!(
+            texts(&parse_csv(table, "en").unwrap().unwrap()),
+            [
+                ("cart.title", "Cart, {{name}}"),
+                (
+                    "cart.items",
+                    "{{n, plural, one{One item} other{{{n}} items}}}"
+                ),
+                ("quote", "Say \"hi\"\nall"),
+                ("todo", "Todo"),
+            ]
+        );
+        assert_eq!(
+            texts(&parse_csv(table, "fr").unwrap().unwrap()),
+            [
+                ("cart.title", "Panier, {{name}}"),
+                (
+                    "cart.items",
+                    "{{n, plural, one{Un article} other{{{n}} articles}}}"
+                ),
+                ("quote", "Dis \"salut\"\r\ntous"),
+                ("todo", ""),
+            ]
+        );
+    }
+}
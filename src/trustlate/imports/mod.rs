@@ -0,0 +1,253 @@
+pub mod csv;
+pub mod po;
+pub mod xliff;
+
+use std::{collections::HashMap, path::Path};
+
+use csv::parse_csv;
+use po::parse_po;
+use xliff::parse_xliff;
+
+use super::{
+    config::Config,
+    errors::{InvalidTranslations, SourceSpan, TrustlateError},
+    exports::key_path,
+    save_translations_file,
+    translations_tree::{
+        find_parameter, LeafType, TranslationTreeNode, TranslationsTree, TreePath,
+    },
+};
+
+/// The formats `import` can read translations from.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ImportFormat {
+    /// The `<lang>.po` gettext catalog of every target language
+    Po,
+    /// The `<lang>.xlf` XLIFF 1.2 or 2.0 document of every target language
+    Xliff,
+    /// A `trustlate.csv` table with a column per language, the base language included
+    Csv,
+}
+
+/// A translation read from an imported file.
+pub struct ImportedTranslation {
+    /// The dot-joined key path of the translation, see [`key_path`].
+    pub key: String,
+    pub text: String,
+    /// The base language text the translation was made from, when the format records it.
+    pub source: Option<String>,
+    /// Where the translation is in the imported file.
+    pub span: Option<SourceSpan>,
+    /// Why the translation cannot be imported, when the format already tells it is not a valid
+    /// one.
+    pub rejection: Option<String>,
+}
+
+/// The translations of a language read from an imported file, ready to be written.
+struct LanguageImport<'a> {
+    lang: &'a String,
+    tree: TranslationsTree,
+    imported: usize,
+    unknown: usize,
+    outdated: usize,
+    rejected: Vec<TrustlateError>,
+}
+
+/// Reads the translations of every target language from `input_dir`, and of the base language too
+/// for the formats that hold every language, and writes the ones that changed to the translation
+/// files.
+///
+/// Languages without a file to import are skipped, as are the translations left empty, the ones
+/// whose key the base language does not have and the ones made from a base language text that
+/// has changed since. Invalid translations are rejected and reported, while the rest of them are
+/// imported. Every file is read before any translation file is written, so an unreadable file
+/// leaves them all untouched.
+pub fn import(
+    config: &Config,
+    trees: &HashMap<String, TranslationsTree>,
+    format: ImportFormat,
+    input_dir: &Path,
+) -> Result<(), TrustlateError> {
+    use colored::*;
+
+    let base_tree = trees.get(&config.base_lang).unwrap();
+    let paths: HashMap<String, TreePath> = base_tree
+        .leaf_paths()
+        .into_iter()
+        .map(|path| (key_path(&path), path))
+        .collect();
+
+    let langs: Vec<&String> = match format {
+        ImportFormat::Csv => config.langs().collect(),
+        ImportFormat::Po | ImportFormat::Xliff => config.target_langs.iter().collect(),
+    };
+    let mut imports = vec![];
+    for lang in langs {
+        let filepath = input_dir.join(match format {
+            ImportFormat::Po => format!("{}.po", lang),
+            ImportFormat::Xliff => format!("{}.xlf", lang),
+            ImportFormat::Csv => "trustlate.csv".to_string(),
+        });
+        if !filepath.exists() {
+            println!(
+                "Nothing to import -> {} ({} not found)",
+                lang.to_uppercase().bold().underline().blue(),
+                filepath.display()
+            );
+            continue;
+        }
+        let source = std::fs::read_to_string(&filepath).map_err(|err| {
+            TrustlateError::ImportCannotOpenFile {
+                lang: lang.to_string(),
+                path: filepath.clone(),
+                reason: err.to_string(),
+            }
+        })?;
+        let invalid = |error: InvalidTranslations| TrustlateError::ImportInvalidFile {
+            lang: lang.to_string(),
+            path: filepath.clone(),
+            error: Box::new(error),
+        };
+        let translations = match format {
+            ImportFormat::Po => parse_po(&source).map_err(invalid)?,
+            ImportFormat::Xliff => parse_xliff(&source).map_err(invalid)?,
+            ImportFormat::Csv => match parse_csv(&source, lang).map_err(invalid)? {
+                Some(translations) => translations,
+                None => {
+                    println!(
+                        "Nothing to import -> {} (no {} column in {})",
+                        lang.to_uppercase().bold().underline().blue(),
+                        lang,
+                        filepath.display()
+                    );
+                    continue;
+                }
+            },
+        };
+
+        let mut import = LanguageImport {
+            lang,
+            tree: trees.get(lang).unwrap().clone(),
+            imported: 0,
+            unknown: 0,
+            outdated: 0,
+            rejected: vec![],
+        };
+        for translation in translations {
+            let Some(path) = paths.get(&translation.key) else {
+                import.unknown += 1;
+                continue;
+            };
+            if let Some(reason) = translation.rejection {
+                import.rejected.push(invalid(InvalidTranslations {
+                    reason,
+                    tree_path: Some(path.clone()),
+                    span: translation.span,
+                }));
+                continue;
+            }
+            let base_leaf = leaf_at(base_tree, path).unwrap();
+            if translation.source.is_some()
+                && translation.source.as_deref() != Some(base_leaf.raw())
+            {
+                import.outdated += 1;
+                continue;
+            }
+            let current_text = leaf_at(&import.tree, path).map(|leaf| leaf.raw());
+            if translation.text.is_empty() || Some(translation.text.as_str()) == current_text {
+                continue;
+            }
+
+            match LeafType::parse(translation.text)
+                .and_then(|leaf| check_placeholders(&leaf, base_leaf).map(|_| leaf))
+            {
+                Ok(leaf) => {
+                    import.tree.set_translation(path, leaf, base_tree);
+                    import.imported += 1;
+                }
+                Err(reason) => import.rejected.push(invalid(InvalidTranslations {
+                    reason,
+                    tree_path: Some(path.clone()),
+                    span: translation.span,
+                })),
+            }
+        }
+        imports.push(import);
+    }
+
+    for import in imports {
+        for rejected in &import.rejected {
+            eprintln!("{}\n", rejected.warning_report());
+        }
+        if import.imported > 0 {
+            save_translations_file(config, import.lang, &import.tree)?;
+        }
+        println!(
+            "Imported {} translations -> {}",
+            import.imported.to_string().bold().green(),
+            import.lang.to_uppercase().bold().underline().blue(),
+        );
+        if import.unknown > 0 {
+            println!(
+                "  {} skipped, their keys are not in the base language",
+                import.unknown.to_string().bold().yellow()
+            );
+        }
+        if import.outdated > 0 {
+            println!(
+                "  {} skipped, their base language texts changed since they were exported",
+                import.outdated.to_string().bold().yellow()
+            );
+        }
+        if !import.rejected.is_empty() {
+            println!(
+                "  {} rejected, see the warnings above",
+                import.rejected.len().to_string().bold().yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that an imported translation is of the same kind as the base language one and that its
+/// placeholders, along with their types, survived the translation.
+fn check_placeholders(leaf: &LeafType, base_leaf: &LeafType) -> Result<(), String> {
+    if leaf != base_leaf {
+        return Err("The translation is not of the same kind as in the base language".to_string());
+    }
+    let names = |leaf: &LeafType, other: &LeafType| -> Vec<String> {
+        leaf.parameters()
+            .iter()
+            .filter(|param| find_parameter(other.parameters(), &param.name).is_none())
+            .map(|param| format!("{{{{{}}}}}", param.name))
+            .collect()
+    };
+    let (missing, extra) = (names(base_leaf, leaf), names(leaf, base_leaf));
+    if !missing.is_empty() {
+        return Err(format!("Missing placeholders: {}", missing.join(", ")));
+    }
+    if !extra.is_empty() {
+        return Err(format!("Unknown placeholders: {}", extra.join(", ")));
+    }
+    let mismatched: Vec<String> = leaf
+        .mismatched_parameter_types(base_leaf)
+        .iter()
+        .filter_map(|name| find_parameter(base_leaf.parameters(), name))
+        .map(|param| format!("{{{{{}}}}} should be a {}", param.name, param.kind))
+        .collect();
+    match mismatched.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "Different placeholder types: {}",
+            mismatched.join(", ")
+        )),
+    }
+}
+
+fn leaf_at<'a>(tree: &'a TranslationsTree, path: &TreePath) -> Option<&'a LeafType> {
+    match tree.find_node_at(path) {
+        Some(TranslationTreeNode::Leaf(leaf)) => Some(leaf),
+        _ => None,
+    }
+}
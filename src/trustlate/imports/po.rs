@@ -0,0 +1,283 @@
+use crate::trustlate::{
+    errors::{InvalidTranslations, SourceSpan},
+    imports::ImportedTranslation,
+};
+
+/// The part of a catalog entry a line of text belongs to.
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Context,
+    Id,
+    Translation,
+    /// Plural forms, which trustlate does not export, as plurals are written as a single text.
+    PluralForm,
+}
+
+#[derive(Default)]
+struct Entry {
+    context: Option<String>,
+    id: Option<String>,
+    translation: Option<String>,
+    translation_line: usize,
+    fuzzy: bool,
+    /// Whether the entry has gettext plural forms.
+    plural: bool,
+}
+
+/// Reads the translations of a gettext catalog, keyed by their context.
+///
+/// The entries without a context, like the header, and the fuzzy and obsolete ones are left out,
+/// as translation tools mark the translations that are not ready that way. Entries with gettext
+/// plural forms (`msgstr[N]`) are rejected, as plurals are imported as a single
+/// `{{count, plural, ...}}` text.
+pub fn parse_po(source: &str) -> Result<Vec<ImportedTranslation>, InvalidTranslations> {
+    let mut translations = vec![];
+    let mut entry = Entry::default();
+    let mut field = None;
+    let finish = |entry: Entry, translations: &mut Vec<ImportedTranslation>| match (
+        entry.context,
+        entry.translation,
+        entry.fuzzy,
+    ) {
+        (Some(key), _, false) if entry.plural => translations.push(ImportedTranslation {
+            key,
+            text: String::new(),
+            source: entry.id,
+            span: SourceSpan::new(source, entry.translation_line, 1),
+            rejection: Some(
+                "Gettext plural forms cannot be imported, write the plural as a single msgstr"
+                    .to_string(),
+            ),
+        }),
+        (Some(key), Some(text), false) => translations.push(ImportedTranslation {
+            key,
+            text,
+            source: entry.id,
+            span: SourceSpan::new(source, entry.translation_line, 1),
+            rejection: None,
+        }),
+        _ => {}
+    };
+
+    for (idx, line) in source.lines().enumerate() {
+        let invalid = |reason: String| InvalidTranslations {
+            reason,
+            tree_path: None,
+            span: SourceSpan::new(source, idx + 1, 1),
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#~") {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            // Comments come before the entry they belong to.
+            if entry.id.is_some() {
+                finish(std::mem::take(&mut entry), &mut translations);
+                field = None;
+            }
+            if let Some(flags) = comment.strip_prefix(',') {
+                entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+            continue;
+        }
+
+        let (keyword, text) = match line.starts_with('"') {
+            true => (None, line),
+            false => {
+                let (keyword, text) = line
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| invalid(format!("Expected a text after \"{}\"", line)))?;
+                (Some(keyword), text.trim())
+            }
+        };
+        let text = parse_string(text)
+            .ok_or_else(|| invalid(format!("Expected a quoted text instead of {}", text)))?;
+
+        field = match keyword {
+            None => field,
+            Some("msgctxt") | Some("msgid") if entry.id.is_some() => {
+                finish(std::mem::take(&mut entry), &mut translations);
+                Some(match keyword {
+                    Some("msgctxt") => Field::Context,
+                    _ => Field::Id,
+                })
+            }
+            Some("msgctxt") => Some(Field::Context),
+            Some("msgid") => Some(Field::Id),
+            Some("msgstr") => {
+                entry.translation_line = idx + 1;
+                Some(Field::Translation)
+            }
+            Some(keyword) if keyword == "msgid_plural" || keyword.starts_with("msgstr[") => {
+                if !entry.plural {
+                    entry.translation_line = idx + 1;
+                }
+                entry.plural = true;
+                Some(Field::PluralForm)
+            }
+            Some(keyword) => return Err(invalid(format!("Unknown keyword \"{}\"", keyword))),
+        };
+        let target = match field {
+            Some(Field::Context) => &mut entry.context,
+            Some(Field::Id) => &mut entry.id,
+            Some(Field::Translation) => &mut entry.translation,
+            Some(Field::PluralForm) => continue,
+            None => return Err(invalid("Expected a keyword before the text".to_string())),
+        };
+        target.get_or_insert_with(String::new).push_str(&text);
+    }
+    finish(entry, &mut translations);
+
+    Ok(translations)
+}
+
+/// Reads a quoted text of a catalog, undoing its escapes.
+fn parse_string(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut parsed = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                'n' => parsed.push('\n'),
+                't' => parsed.push('\t'),
+                'r' => parsed.push('\r'),
+                escaped => parsed.push(escaped),
+            },
+            '"' => return None,
+            _ => parsed.push(c),
+        }
+    }
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::{exports::po::export_po, translations_tree::TranslationsTree};
+
+    fn texts(translations: &[ImportedTranslation]) -> Vec<(&str, &str)> {
+        translations
+            .iter()
+            .map(|translation| (translation.key.as_str(), translation.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_keyed_by_their_context() {
+        let translations = parse_po(concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Language: fr\\n\"\n",
+            "\n",
+            "# A comment\n",
+            "msgctxt \"cart.title\"\n",
+            "msgid \"Cart of {{name}}\"\n",
+            "msgstr \"\"\n",
+            "\"Panier de \"\n",
+            "\"{{name}}\"\n",
+            "\n",
+            "msgctxt \"quote\"\n",
+            "msgid \"Say \\\"hi\\\"\"\n",
+            "msgstr \"Dis \\\"salut\\\"\\n\\tà tous\"\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            texts(&translations),
+            [
+                ("cart.title", "Panier de {{name}}"),
+                ("quote", "Dis \"salut\"\n\tà tous")
+            ]
+        );
+        assert_eq!(translations[0].source.as_deref(), Some("Cart of {{name}}"));
+        assert_eq!(translations[0].span.as_ref().unwrap().line, 8);
+    }
+
+    #[test]
+    fn fuzzy_and_obsolete_entries_are_left_out() {
+        let translations = parse_po(concat!(
+            "#, fuzzy, c-format\n",
+            "msgctxt \"a\"\n",
+            "msgid \"A\"\n",
+            "msgstr \"Un\"\n",
+            "\n",
+            "#~ msgctxt \"b\"\n",
+            "#~ msgid \"B\"\n",
+            "#~ msgstr \"Bé\"\n",
+            "\n",
+            "msgctxt \"c\"\n",
+            "msgid \"C\"\n",
+            "msgstr \"Cé\"\n",
+        ))
+        .unwrap();
+        assert_eq!(texts(&translations), [("c", "Cé")]);
+    }
+
+    #[test]
+    fn plural_forms_are_rejected() {
+        let translations = parse_po(concat!(
+            "msgctxt \"items\"\n",
+            "msgid \"{{n}} item\"\n",
+            "msgid_plural \"{{n}} items\"\n",
+            "msgstr[0] \"{{n}} article\"\n",
+            "msgstr[1] \"{{n}} articles\"\n",
+            "\n",
+            "msgctxt \"hi\"\n",
+            "msgid \"Hi\"\n",
+            "msgstr \"Salut\"\n",
+        ))
+        .unwrap();
+        assert_eq!(translations.len(), 2);
+        assert_eq!(translations[0].key, "items");
+        assert!(translations[0].rejection.is_some());
+        assert_eq!(translations[0].span.as_ref().unwrap().line, 3);
+        assert_eq!(translations[1].key, "hi");
+        assert_eq!(translations[1].text, "Salut");
+        assert!(translations[1].rejection.is_none());
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        let error = parse_po("msgctxt \"a\"\nmsgid \"A\"\nmsgtext \"Un\"\n")
+            .err()
+            .unwrap();
+        assert_eq!(error.reason, "Unknown keyword \"msgtext\"");
+        assert_eq!(error.span.unwrap().line, 3);
+
+        let error = parse_po("msgctxt \"a\"\nmsgid A\n").err().unwrap();
+        assert_eq!(error.reason, "Expected a quoted text instead of A");
+        assert_eq!(error.span.unwrap().line, 2);
+
+        let error = parse_po("\"Orphan\"\n").err().unwrap();
+        assert_eq!(error.reason, "Expected a keyword before the text");
+    }
+
+    #[test]
+    fn exported_catalogs_are_imported_back() {
+        let base = TranslationsTree::from_json(
+            r#"{"cart": {"title": "Cart of {{name}}", "items": "{{n, plural, one{One item} other{{{n}} items}}}"}, "quote": "Say \"hi\"\n\\o/", "todo": "Todo"}"#,
+        )
+        .unwrap();
+        let fr = TranslationsTree::from_json(
+            r#"{"cart": {"title": "Panier de {{name}}", "items": "{{n, plural, one{Un article} other{{{n}} articles}}}"}, "quote": "Dis \"salut\"\n\\o/", "todo": "TODO"}"#,
+        )
+        .unwrap();
+        let (en_lang, fr_lang) = ("en".to_string(), "fr".to_string());
+        let files = export_po(&[(&en_lang, base), (&fr_lang, fr)], "TODO");
+        let catalog = files.iter().find(|file| file.path == "fr.po").unwrap();
+        let translations = parse_po(&catalog.content).unwrap();
+        assert_eq!(
+            texts(&translations),
+            [
+                ("cart.title", "Panier de {{name}}"),
+                (
+                    "cart.items",
+                    "{{n, plural, one{Un article} other{{{n}} articles}}}"
+                ),
+                ("quote", "Dis \"salut\"\n\\o/"),
+                ("todo", ""),
+            ]
+        );
+        assert_eq!(translations[0].source.as_deref(), Some("Cart of {{name}}"));
+    }
+}
@@ -0,0 +1,402 @@
+use crate::trustlate::{
+    errors::{InvalidTranslations, SourceSpan},
+    imports::ImportedTranslation,
+};
+
+/// A piece of an XML document.
+enum XmlEvent {
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+        empty: bool,
+    },
+    End(String),
+    Text(String),
+}
+
+/// The text of a source or target being read, with the offset of the element it belongs to.
+#[derive(Default)]
+struct Content {
+    text: String,
+    offset: usize,
+}
+
+#[derive(Default)]
+struct Unit {
+    id: Option<String>,
+    source: Option<Content>,
+    target: Option<Content>,
+    state: Option<String>,
+    /// Whether the segment being read has a target, while reading one.
+    segment_target: Option<bool>,
+    /// Whether one of the segments read so far is not translated yet.
+    untranslated: bool,
+}
+
+/// Reads the translations of an XLIFF 1.2 or 2.0 document, keyed by the id of their units.
+///
+/// `<ph>` elements are turned back into the placeholders they stand for, taken from their content
+/// in XLIFF 1.2 and from their `equiv` attribute in XLIFF 2.0. Targets that translation tools mark
+/// as not translated yet are left out, as are the units with a segment that is not translated yet,
+/// so that the segments of a unit are imported all together or not at all.
+pub fn parse_xliff(source: &str) -> Result<Vec<ImportedTranslation>, InvalidTranslations> {
+    let invalid = |reason: String, offset: usize| InvalidTranslations {
+        reason,
+        tree_path: None,
+        span: SourceSpan::at_offset(source, offset),
+    };
+
+    let mut translations = vec![];
+    let mut unit: Option<Unit> = None;
+    let mut reading: Option<(Content, bool)> = None;
+    for (event, offset) in xml_events(source).map_err(|(reason, offset)| invalid(reason, offset))? {
+        match event {
+            XmlEvent::Start {
+                name, attributes, ..
+            } if name == "trans-unit" || name == "unit" => {
+                unit = Some(Unit {
+                    id: attribute(&attributes, "id"),
+                    ..Default::default()
+                });
+            }
+            XmlEvent::Start {
+                name, attributes, ..
+            } if name == "segment" => {
+                if let Some(unit) = unit.as_mut() {
+                    unit.state = attribute(&attributes, "state");
+                    unit.segment_target = Some(false);
+                }
+            }
+            XmlEvent::Start {
+                name,
+                attributes,
+                empty,
+            } if name == "source" || name == "target" => {
+                if let (Some(unit), false) = (unit.as_mut(), empty) {
+                    if name == "target" && unit.state.is_none() {
+                        unit.state = attribute(&attributes, "state");
+                    }
+                    if let (Some(has_target), true) =
+                        (unit.segment_target.as_mut(), name == "target")
+                    {
+                        *has_target = true;
+                    }
+                    reading = Some((
+                        Content {
+                            text: String::new(),
+                            offset,
+                        },
+                        name == "target",
+                    ));
+                }
+            }
+            XmlEvent::Start {
+                name,
+                attributes,
+                empty: true,
+            } if name == "ph" => {
+                if let Some((content, _)) = reading.as_mut() {
+                    content.text += &attribute(&attributes, "equiv").unwrap_or_default();
+                }
+            }
+            XmlEvent::Text(text) => {
+                if let Some((content, _)) = reading.as_mut() {
+                    content.text += &text;
+                }
+            }
+            XmlEvent::End(name) if name == "source" || name == "target" => {
+                if let (Some(unit), Some((content, is_target))) = (unit.as_mut(), reading.take()) {
+                    // The segments of a unit are joined back together.
+                    let read = match is_target {
+                        true => &mut unit.target,
+                        false => &mut unit.source,
+                    };
+                    match read {
+                        Some(read) => read.text += &content.text,
+                        None => *read = Some(content),
+                    }
+                }
+            }
+            XmlEvent::End(name) if name == "segment" => {
+                if let Some(unit) = unit.as_mut() {
+                    if unit.segment_target.take() == Some(false) || untranslated(&unit.state) {
+                        unit.untranslated = true;
+                    }
+                    unit.state = None;
+                }
+            }
+            XmlEvent::End(name) if name == "trans-unit" || name == "unit" => {
+                let Some(Unit {
+                    id: Some(key),
+                    source: unit_source,
+                    target: Some(target),
+                    state,
+                    untranslated: false,
+                    ..
+                }) = unit.take()
+                else {
+                    continue;
+                };
+                if untranslated(&state) {
+                    continue;
+                }
+                translations.push(ImportedTranslation {
+                    key,
+                    text: target.text,
+                    source: unit_source.map(|content| content.text),
+                    span: SourceSpan::at_offset(source, target.offset),
+                    rejection: None,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(translations)
+}
+
+/// Whether a target or segment state means it is not translated yet.
+fn untranslated(state: &Option<String>) -> bool {
+    matches!(
+        state.as_deref(),
+        Some("new" | "needs-translation" | "initial")
+    )
+}
+
+fn attribute(attributes: &[(String, String)], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|(attribute, _)| attribute == name)
+        .map(|(_, value)| value.clone())
+}
+
+/// Splits an XML document into its elements and texts, leaving out the declaration, comments and
+/// processing instructions. Namespace prefixes are left out of the element names.
+fn xml_events(source: &str) -> Result<Vec<(XmlEvent, usize)>, (String, usize)> {
+    let mut events = vec![];
+    let mut pos = 0;
+    while pos < source.len() {
+        let rest = &source[pos..];
+        let skip_until = |end: &str, what: &str| {
+            rest.find(end)
+                .map(|idx| idx + end.len())
+                .ok_or_else(|| (format!("Unclosed {}", what), pos))
+        };
+        if rest.starts_with("<!--") {
+            pos += skip_until("-->", "comment")?;
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata
+                .find("]]>")
+                .ok_or_else(|| ("Unclosed CDATA section".to_string(), pos))?;
+            events.push((XmlEvent::Text(cdata[..end].to_string()), pos));
+            pos += "<![CDATA[".len() + end + "]]>".len();
+        } else if rest.starts_with("<?") {
+            pos += skip_until("?>", "processing instruction")?;
+        } else if rest.starts_with("<!") {
+            pos += skip_until(">", "declaration")?;
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = tag
+                .find('>')
+                .ok_or_else(|| ("Unclosed tag".to_string(), pos))?;
+            events.push((XmlEvent::End(local_name(tag[..end].trim())), pos));
+            pos += 2 + end + 1;
+        } else if rest.starts_with('<') {
+            let (event, len) = start_tag(rest).ok_or_else(|| ("Invalid tag".to_string(), pos))?;
+            events.push((event, pos));
+            pos += len;
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text =
+                unescape_xml(&rest[..end]).ok_or_else(|| ("Invalid entity".to_string(), pos))?;
+            events.push((XmlEvent::Text(text), pos));
+            pos += end;
+        }
+    }
+    Ok(events)
+}
+
+/// Reads the start tag `rest` begins with, returning it and its length.
+fn start_tag(rest: &str) -> Option<(XmlEvent, usize)> {
+    let mut chars = rest.char_indices().skip(1).peekable();
+    let mut name = String::new();
+    while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && *c != '/' && *c != '>') {
+        name.push(c);
+    }
+
+    let mut attributes = vec![];
+    loop {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        match chars.next()? {
+            (idx, '>') => {
+                let event = XmlEvent::Start {
+                    name: local_name(&name),
+                    attributes,
+                    empty: false,
+                };
+                return Some((event, idx + 1));
+            }
+            (_, '/') => {
+                let (idx, _) = chars.next().filter(|(_, c)| *c == '>')?;
+                let event = XmlEvent::Start {
+                    name: local_name(&name),
+                    attributes,
+                    empty: true,
+                };
+                return Some((event, idx + 1));
+            }
+            (_, first) => {
+                let mut attribute = first.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| *c != '=' && !c.is_whitespace()) {
+                    attribute.push(c);
+                }
+                while chars
+                    .next_if(|(_, c)| c.is_whitespace() || *c == '=')
+                    .is_some()
+                {}
+                let (start, quote) = chars.next().filter(|(_, c)| *c == '"' || *c == '\'')?;
+                let (end, _) = chars.find(|(_, c)| *c == quote)?;
+                attributes.push((attribute, unescape_xml(&rest[start + 1..end])?));
+            }
+        }
+    }
+}
+
+/// The name of an element without its namespace prefix, which XLIFF documents do not agree on.
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+fn unescape_xml(text: &str) -> Option<String> {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(idx) = rest.find('&') {
+        unescaped += &rest[..idx];
+        let end = rest[idx..].find(';')? + idx;
+        let entity = &rest[idx + 1..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => entity.strip_prefix('#')?.parse().ok()?,
+                };
+                char::from_u32(code)?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    Some(unescaped + rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trustlate::{
+        exports::xliff::{export_xliff, XliffVersion},
+        translations_tree::TranslationsTree,
+    };
+
+    fn texts(translations: &[ImportedTranslation]) -> Vec<(&str, &str)> {
+        translations
+            .iter()
+            .map(|translation| (translation.key.as_str(), translation.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn xliff_1_units_are_keyed_by_their_id() {
+        let translations = parse_xliff(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<xliff version=\"1.2\"><file><body>\n",
+            "<!-- <trans-unit id=\"commented\"> -->\n",
+            "<trans-unit id=\"cart.title\">\n",
+            "  <source>Cart of <ph id=\"name\">{{name}}</ph></source>\n",
+            "  <target state=\"translated\">Panier de <ph id=\"name\">{{name}}</ph> &amp; <![CDATA[<b>]]></target>\n",
+            "</trans-unit>\n",
+            "<trans-unit id=\"new\"><source>New</source><target state=\"new\">Nouveau</target></trans-unit>\n",
+            "<trans-unit id=\"missing\"><source>Missing</source></trans-unit>\n",
+            "</body></file></xliff>\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            texts(&translations),
+            [("cart.title", "Panier de {{name}} & <b>")]
+        );
+        assert_eq!(translations[0].source.as_deref(), Some("Cart of {{name}}"));
+        assert_eq!(translations[0].span.as_ref().unwrap().line, 6);
+    }
+
+    #[test]
+    fn xliff_2_segments_are_joined() {
+        let translations = parse_xliff(concat!(
+            "<xliff version=\"2.0\" xmlns=\"urn:oasis:names:tc:xliff:document:2.0\"><file id=\"f\">\n",
+            "<unit id=\"greeting\">\n",
+            "  <segment state=\"translated\"><source>Hi </source><target>Salut </target></segment>\n",
+            "  <segment state=\"final\"><source><ph id=\"name\" equiv=\"{{name}}\" disp=\"name\"/></source><target><ph id=\"name\" equiv=\"{{name}}\" disp=\"name\"/></target></segment>\n",
+            "</unit>\n",
+            "<unit id=\"partial\">\n",
+            "  <segment state=\"translated\"><source>A</source><target>Un</target></segment>\n",
+            "  <segment state=\"initial\"><source>B</source><target>B</target></segment>\n",
+            "</unit>\n",
+            "<unit id=\"untargeted\">\n",
+            "  <segment><source>A</source><target>Un</target></segment>\n",
+            "  <segment><source>B</source></segment>\n",
+            "</unit>\n",
+            "</file></xliff>\n",
+        ))
+        .unwrap();
+        assert_eq!(texts(&translations), [("greeting", "Salut {{name}}")]);
+    }
+
+    #[test]
+    fn malformed_documents_are_reported() {
+        let error = parse_xliff("<xliff>\n<file>\n<!-- unclosed\n</file>")
+            .err()
+            .unwrap();
+        assert_eq!(error.reason, "Unclosed comment");
+        assert_eq!(error.span.unwrap().line, 3);
+
+        let error = parse_xliff("<xliff><file><body></bo").err().unwrap();
+        assert_eq!(error.reason, "Unclosed tag");
+    }
+
+    #[test]
+    fn exported_documents_are_imported_back() {
+        let base = TranslationsTree::from_json(
+            r#"{"cart": {"title": "{{name}} & {{name}}", "items": "{{n, plural, one{One item} other{{{n}} items}}}"}, "todo": "Todo <b>"}"#,
+        )
+        .unwrap();
+        let fr = TranslationsTree::from_json(
+            r#"{"cart": {"title": "{{name}} et {{name}} ou {{name}}", "items": "{{n, plural, one{Un article} other{{{n}} articles}}}"}, "todo": "TODO"}"#,
+        )
+        .unwrap();
+        let (en_lang, fr_lang) = ("en".to_string(), "fr".to_string());
+        for version in [XliffVersion::V1, XliffVersion::V2] {
+            let files = export_xliff(
+                &[(&en_lang, base.clone()), (&fr_lang, fr.clone())],
+                "TODO",
+                version,
+            );
+            let translations = parse_xliff(&files[0].content).unwrap();
+            assert_eq!(
+                texts(&translations),
+                [
+                    ("cart.title", "{{name}} et {{name}} ou {{name}}"),
+                    (
+                        "cart.items",
+                        "{{n, plural, one{Un article} other{{{n}} articles}}}"
+                    ),
+                ]
+            );
+            assert_eq!(
+                translations[0].source.as_deref(),
+                Some("{{name}} & {{name}}")
+            );
+        }
+    }
+}
@@ -0,0 +1,75 @@
+/// Returns the lowercase ISO 639-1 code of the language of a locale, which is what the locale
+/// tables of trustlate are keyed by.
+///
+/// Besides stripping the region (`es-ES`, `pt_BR`), it maps the non standard codes that are
+/// commonly used for translation files (e.g. `cat` for Catalan or `kr` for Korean).
+pub fn base_language(lang: &str) -> String {
+    let lang = lang
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match lang.as_str() {
+        "cat" => "ca".to_string(),
+        "kr" => "ko".to_string(),
+        "iw" => "he".to_string(),
+        "jp" => "ja".to_string(),
+        "cn" => "zh".to_string(),
+        _ => lang,
+    }
+}
+
+/// Returns the BCP 47 tag to hand to locale aware APIs (e.g. `Intl` in Javascript) for a
+/// language of the configuration.
+pub fn language_tag(lang: &str) -> String {
+    let mut subtags = lang.split(['-', '_']);
+    let language = base_language(subtags.next().unwrap_or_default());
+    subtags.fold(language, |acc, subtag| {
+        format!("{}-{}", acc, subtag.to_uppercase())
+    })
+}
+
+/// The conventions a locale follows when writing numbers, dates and amounts of money.
+///
+/// It is only used by the generators whose target language lacks locale aware formatting in its
+/// standard library.
+#[derive(Debug)]
+pub struct LocaleFormats {
+    pub decimal_separator: &'static str,
+    pub group_separator: &'static str,
+    /// A date pattern made of `dd`, `MM` and `yyyy` (e.g. `dd/MM/yyyy`).
+    pub date_pattern: &'static str,
+    /// Whether the currency code goes before the amount.
+    pub currency_first: bool,
+}
+
+const fn formats(
+    decimal_separator: &'static str,
+    group_separator: &'static str,
+    date_pattern: &'static str,
+    currency_first: bool,
+) -> LocaleFormats {
+    LocaleFormats {
+        decimal_separator,
+        group_separator,
+        date_pattern,
+        currency_first,
+    }
+}
+
+pub fn locale_formats(lang: &str) -> LocaleFormats {
+    match base_language(lang).as_str() {
+        "en" => formats(".", ",", "MM/dd/yyyy", true),
+        "es" | "ca" | "it" | "pt" | "el" => formats(",", ".", "dd/MM/yyyy", false),
+        "de" | "da" | "tr" => formats(",", ".", "dd.MM.yyyy", false),
+        "nl" => formats(",", ".", "dd-MM-yyyy", true),
+        "fr" => formats(",", " ", "dd/MM/yyyy", false),
+        "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "fi" | "nb" | "no" | "sv" => {
+            formats(",", " ", "dd.MM.yyyy", false)
+        }
+        "ja" | "zh" => formats(".", ",", "yyyy/MM/dd", true),
+        "ko" => formats(".", ",", "yyyy. MM. dd.", true),
+        _ => formats(".", ",", "yyyy-MM-dd", true),
+    }
+}
@@ -1,113 +1,192 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
 
 use crate::trustlate::translations_tree::TreeComparisonDifference;
 
-use self::{config::Config, errors::TrustlateError, translations_tree::TranslationsTree};
+use self::{
+    config::Config,
+    errors::TrustlateError,
+    exports::ExportFormat,
+    imports::ImportFormat,
+    reports::{CheckFormat, LanguageCheck, Severity},
+    rewrite::rewrite_like,
+    translations_tree::TranslationsTree,
+};
 
 pub mod config;
 // pub mod parser;
 pub mod codegen;
 pub mod errors;
+pub mod exports;
+pub mod imports;
+pub mod locales;
+pub mod plurals;
+pub mod reports;
+pub mod rewrite;
 pub mod translations_tree;
 
+/// Loads the translation files of every configured language.
+///
+/// Every file is loaded even if some of them fail, so that all the problems can be reported at
+/// once.
 pub fn generate_trees(
     config: &Config,
-) -> Result<HashMap<String, TranslationsTree>, errors::TrustlateError> {
+) -> Result<HashMap<String, TranslationsTree>, TrustlateError> {
     let mut trees = HashMap::new();
+    let mut errors = vec![];
+    let mut seen_langs = HashSet::new();
+    let mut repeated_langs = HashSet::new();
+
+    for lang in config.langs() {
+        if !seen_langs.insert(lang) {
+            // A repeated language is reported once, however many times it is repeated.
+            if repeated_langs.insert(lang) {
+                errors.push(TrustlateError::ParseTranslationFileRepeatedLanguageKey {
+                    lang: lang.to_string(),
+                });
+            }
+            continue;
+        }
 
-    for lang in std::iter::once(&config.base_lang).chain(config.target_langs.iter()) {
-        let f = std::fs::File::open(config.source_dir.join(format!("{}.json", lang)))
-            .map_err(|_| TrustlateError::ParseTranslationFileCannotOpen)?;
-        if let Some(_) = trees.insert(lang.to_string(), TranslationsTree::from_file(&f)?) {
-            // If this is reached it means that one of the target languages is repeated.
-            // Let's throw an error in such case.
-            return Err(TrustlateError::ParseTranslationFileRepeatedLanguageKey);
+        let path = config.source_dir.join(format!("{}.json", lang));
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                errors.push(TrustlateError::ParseTranslationFileCannotOpen {
+                    lang: lang.to_string(),
+                    path,
+                    reason: err.to_string(),
+                });
+                continue;
+            }
+        };
+        match TranslationsTree::from_json(&source) {
+            Ok(tree) => {
+                trees.insert(lang.to_string(), tree);
+            }
+            Err(invalid) => errors.extend(invalid.into_iter().map(|error| {
+                TrustlateError::ParseTranslationFileInvalidJson {
+                    lang: lang.to_string(),
+                    path: path.clone(),
+                    error: Box::new(error),
+                }
+            })),
         }
     }
 
-    Ok(trees)
+    match errors.len() {
+        0 => Ok(trees),
+        1 => Err(errors.remove(0)),
+        _ => Err(TrustlateError::LoadTranslationFiles(errors)),
+    }
 }
 
+/// Checks the translations of every target language against the base language ones, reporting the
+/// differences in the given format.
+///
+/// Returns whether the check passed, that is, whether no difference has the error severity. Being
+/// `strict` makes warnings count as errors.
 pub fn check_trees(
     config: &Config,
     trees: &HashMap<String, TranslationsTree>,
     show_differences: bool,
+    format: CheckFormat,
+    strict: bool,
 ) -> bool {
-    let mut ok = true;
     let base_lang_tree = trees.get(&config.base_lang).unwrap();
+    let checks: Vec<LanguageCheck> = config
+        .target_langs
+        .iter()
+        .map(|target_lang| {
+            let target_tree = trees.get(target_lang).unwrap();
+            let differences = base_lang_tree
+                .compare(target_tree, target_lang)
+                .into_iter()
+                .chain(target_tree.untranslated(config.filling()))
+                .map(|diff| {
+                    let severity = match config.severity_of(&diff) {
+                        Severity::Warning if strict => Severity::Error,
+                        severity => severity,
+                    };
+                    (diff, severity)
+                })
+                .filter(|(_, severity)| *severity != Severity::Ignore)
+                .collect();
+            LanguageCheck {
+                lang: target_lang,
+                file: config.source_dir.join(format!("{}.json", target_lang)),
+                differences,
+            }
+        })
+        .collect();
+    let ok = checks.iter().all(|check| check.count(Severity::Error) == 0);
 
-    for target_lang in &config.target_langs {
-        use colored::*;
+    match format {
+        CheckFormat::Table => print_check_table(&checks, show_differences),
+        CheckFormat::Json => println!("{}", reports::json_report(config, &checks, ok)),
+        CheckFormat::Sarif => println!("{}", reports::sarif_report(&checks)),
+        CheckFormat::Junit => println!("{}", reports::junit_report(base_lang_tree, &checks)),
+    }
 
-        let target_tree = trees.get(target_lang).unwrap();
-        let differences = base_lang_tree.compare(target_tree);
+    ok
+}
+
+fn print_check_table(checks: &[LanguageCheck], show_differences: bool) {
+    use colored::*;
+
+    for check in checks {
+        let warnings = check.count(Severity::Warning);
         println!(
             "Translations for: {} -> {}",
-            target_lang.to_uppercase().bold().underline().blue(),
-            if differences.len() == 0 {
-                "OK".bold().green()
-            } else {
+            check.lang.to_uppercase().bold().underline().blue(),
+            if check.count(Severity::Error) > 0 {
                 "NOT OK".bold().red()
+            } else if warnings > 0 {
+                format!("OK ({} warnings)", warnings).bold().yellow()
+            } else {
+                "OK".bold().green()
             },
         );
 
-        if differences.len() != 0 && show_differences {
+        if !check.differences.is_empty() && show_differences {
             use prettytable::*;
 
             let mut table = Table::new();
             table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-            table.set_titles(row!["Path", "Error"]);
-            for diff in &differences {
-                match diff {
-                    TreeComparisonDifference::DifferentNodeType(path) => {
-                        table.add_row(Row::new(vec![
-                            Cell::new(&format!("{}", path)),
-                            Cell::new("Different value"),
-                        ]));
-                    }
-                    TreeComparisonDifference::MissingNode(path) => {
-                        table.add_row(Row::new(vec![
-                            Cell::new(&format!("{}", path)),
-                            Cell::new("Missing values"),
-                        ]));
-                    }
-                    TreeComparisonDifference::DifferentParameters(path) => {
-                        table.add_row(Row::new(vec![
-                            Cell::new(&format!("{}", path)),
-                            Cell::new("Incompatible parameters"),
-                        ]));
-                    }
-                }
+            table.set_titles(row!["Path", "Severity", "Error"]);
+            for (diff, severity) in &check.differences {
+                table.add_row(Row::new(vec![
+                    Cell::new(&diff.path().to_string()),
+                    Cell::new(severity.keyword()),
+                    Cell::new(&diff.to_string()),
+                ]));
             }
             table.printstd();
             println!();
-            ok = false;
         }
     }
-    ok
 }
 
 pub fn harmonize_files(
     config: &Config,
     trees: &mut HashMap<String, TranslationsTree>,
     filling_string: &str,
+    prune: bool,
 ) -> Result<(), TrustlateError> {
     let base_lang_tree = trees.get(&config.base_lang).unwrap();
 
     for target_lang in &config.target_langs {
         let mut target_lang_tree = trees.get(target_lang).unwrap().clone();
-        let diffs = base_lang_tree.compare(&target_lang_tree);
-        if diffs.len() != 0 {
+        let mut diffs = base_lang_tree.compare(&target_lang_tree, target_lang);
+        if !prune {
+            diffs.retain(|diff| !matches!(diff, TreeComparisonDifference::ExtraNode(_)));
+        }
+        if !diffs.is_empty() {
             use colored::*;
 
-            target_lang_tree.harmonize(base_lang_tree, &diffs, filling_string);
-            let f = std::fs::OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .open(config.source_dir.join(format!("{}.json", target_lang)))
-                .map_err(|_| TrustlateError::FixTreeCannotOpenSourceFile)?;
-            serde_json::to_writer_pretty(f, &target_lang_tree)
-                .map_err(|_| TrustlateError::FixTreeCannotWriteToSourceFile)?;
+            target_lang_tree.harmonize(base_lang_tree, &diffs, filling_string, target_lang, prune);
+            save_translations_file(config, target_lang, &target_lang_tree)?;
             println!(
                 "Fixed {} differences -> {}",
                 diffs.len().to_string().bold().green(),
@@ -119,9 +198,90 @@ pub fn harmonize_files(
     Ok(())
 }
 
+/// Rewrites the translations file of a language with the given tree, see [`write_like`].
+fn save_translations_file(
+    config: &Config,
+    lang: &str,
+    tree: &TranslationsTree,
+) -> Result<(), TrustlateError> {
+    let filepath = config.source_dir.join(format!("{}.json", lang));
+    let original = std::fs::read_to_string(&filepath).map_err(|err| {
+        TrustlateError::FixTreeCannotOpenSourceFile {
+            lang: lang.to_string(),
+            path: filepath.clone(),
+            reason: err.to_string(),
+        }
+    })?;
+    let mut f = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(&filepath)
+        .map_err(|err| TrustlateError::FixTreeCannotOpenSourceFile {
+            lang: lang.to_string(),
+            path: filepath.clone(),
+            reason: err.to_string(),
+        })?;
+    write_like(&mut f, tree, &original).map_err(|err| {
+        TrustlateError::FixTreeCannotWriteToSourceFile {
+            lang: lang.to_string(),
+            path: filepath.clone(),
+            reason: err.to_string(),
+        }
+    })
+}
+
+/// Writes a translations tree as JSON over the translations file `original`, only changing the
+/// lines whose translations changed, see [`rewrite_like`].
+///
+/// Files that cannot be rewritten that way are written from scratch, with the same indentation and
+/// final newline as `original`.
+fn write_like(
+    writer: &mut impl std::io::Write,
+    tree: &TranslationsTree,
+    original: &str,
+) -> std::io::Result<()> {
+    let indent: String = original
+        .lines()
+        .skip(1)
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+        .filter(|indent: &String| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string());
+
+    if let Some(json) = rewrite_like(original, tree, &indent) {
+        return writer.write_all(json.as_bytes());
+    }
+
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut *writer, formatter);
+    tree.serialize(&mut serializer)?;
+    if original.ends_with('\n') {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
 pub fn generate_code(
     config: &Config,
     trees: &HashMap<String, TranslationsTree>,
 ) -> Result<(), TrustlateError> {
     codegen::generate(config, trees)
 }
+
+pub fn export_translations(
+    config: &Config,
+    trees: &HashMap<String, TranslationsTree>,
+    format: ExportFormat,
+    output_dir: &std::path::Path,
+) -> Result<(), TrustlateError> {
+    exports::export(config, trees, format, output_dir)
+}
+
+pub fn import_translations(
+    config: &Config,
+    trees: &HashMap<String, TranslationsTree>,
+    format: ImportFormat,
+    input_dir: &std::path::Path,
+) -> Result<(), TrustlateError> {
+    imports::import(config, trees, format, input_dir)
+}
@@ -0,0 +1,473 @@
+use std::fmt::Display;
+
+use super::locales::base_language;
+
+/// CLDR plural categories, in the canonical order used when rendering plural forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "zero" => Some(Self::Zero),
+            "one" => Some(Self::One),
+            "two" => Some(Self::Two),
+            "few" => Some(Self::Few),
+            "many" => Some(Self::Many),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl Display for PluralCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.keyword())
+    }
+}
+
+/// A plural category together with the condition that selects it.
+///
+/// Conditions are written in a C-like syntax over a non-negative integer `n` (e.g.
+/// `n % 10 == 1 && n % 100 != 11`) so that generators can paste them into the target language,
+/// see [`plural_branches`].
+#[derive(Debug)]
+pub struct PluralRule {
+    pub category: PluralCategory,
+    pub condition: &'static str,
+}
+
+const fn rule(category: PluralCategory, condition: &'static str) -> PluralRule {
+    PluralRule {
+        category,
+        condition,
+    }
+}
+
+const RULES_OTHER_ONLY: &[PluralRule] = &[];
+
+const RULES_ONE: &[PluralRule] = &[rule(PluralCategory::One, "n == 1")];
+
+const RULES_ZERO_ONE: &[PluralRule] = &[rule(PluralCategory::One, "n == 0 || n == 1")];
+
+const RULES_EAST_SLAVIC: &[PluralRule] = &[
+    rule(PluralCategory::One, "n % 10 == 1 && n % 100 != 11"),
+    rule(
+        PluralCategory::Few,
+        "n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14)",
+    ),
+    rule(
+        PluralCategory::Many,
+        "n % 10 == 0 || n % 10 >= 5 || (n % 100 >= 11 && n % 100 <= 14)",
+    ),
+];
+
+const RULES_POLISH: &[PluralRule] = &[
+    rule(PluralCategory::One, "n == 1"),
+    rule(
+        PluralCategory::Few,
+        "n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14)",
+    ),
+    rule(PluralCategory::Many, "n != 1"),
+];
+
+const RULES_WEST_SLAVIC: &[PluralRule] = &[
+    rule(PluralCategory::One, "n == 1"),
+    rule(PluralCategory::Few, "n >= 2 && n <= 4"),
+];
+
+const RULES_ROMANIAN: &[PluralRule] = &[
+    rule(PluralCategory::One, "n == 1"),
+    rule(
+        PluralCategory::Few,
+        "n == 0 || (n != 1 && n % 100 >= 1 && n % 100 <= 19)",
+    ),
+];
+
+const RULES_SLOVENIAN: &[PluralRule] = &[
+    rule(PluralCategory::One, "n % 100 == 1"),
+    rule(PluralCategory::Two, "n % 100 == 2"),
+    rule(PluralCategory::Few, "n % 100 == 3 || n % 100 == 4"),
+];
+
+const RULES_LATVIAN: &[PluralRule] = &[
+    rule(
+        PluralCategory::Zero,
+        "n % 10 == 0 || (n % 100 >= 11 && n % 100 <= 19)",
+    ),
+    rule(PluralCategory::One, "n % 10 == 1 && n % 100 != 11"),
+];
+
+const RULES_LITHUANIAN: &[PluralRule] = &[
+    rule(
+        PluralCategory::One,
+        "n % 10 == 1 && (n % 100 < 11 || n % 100 > 19)",
+    ),
+    rule(
+        PluralCategory::Few,
+        "n % 10 >= 2 && (n % 100 < 11 || n % 100 > 19)",
+    ),
+];
+
+const RULES_IRISH: &[PluralRule] = &[
+    rule(PluralCategory::One, "n == 1"),
+    rule(PluralCategory::Two, "n == 2"),
+    rule(PluralCategory::Few, "n >= 3 && n <= 6"),
+    rule(PluralCategory::Many, "n >= 7 && n <= 10"),
+];
+
+const RULES_WELSH: &[PluralRule] = &[
+    rule(PluralCategory::Zero, "n == 0"),
+    rule(PluralCategory::One, "n == 1"),
+    rule(PluralCategory::Two, "n == 2"),
+    rule(PluralCategory::Few, "n == 3"),
+    rule(PluralCategory::Many, "n == 6"),
+];
+
+const RULES_HEBREW: &[PluralRule] = &[
+    rule(PluralCategory::One, "n == 1"),
+    rule(PluralCategory::Two, "n == 2"),
+];
+
+const RULES_ARABIC: &[PluralRule] = &[
+    rule(PluralCategory::Zero, "n == 0"),
+    rule(PluralCategory::One, "n == 1"),
+    rule(PluralCategory::Two, "n == 2"),
+    rule(PluralCategory::Few, "n % 100 >= 3 && n % 100 <= 10"),
+    rule(PluralCategory::Many, "n % 100 >= 11"),
+];
+
+/// Returns the plural rules of a language, ordered as they have to be evaluated.
+///
+/// The `other` category is implicit: it is selected when none of the returned rules match. Only
+/// integer counts are taken into account, so categories that CLDR reserves for decimals are left
+/// out. Unknown languages fall back to the `one`/`other` rules.
+pub fn plural_rules(lang: &str) -> &'static [PluralRule] {
+    // European Portuguese does not put 0 in `one`, unlike the Brazilian Portuguese `pt` stands for.
+    if lang.replace('_', "-").eq_ignore_ascii_case("pt-PT") {
+        return RULES_ONE;
+    }
+    match base_language(lang).as_str() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => RULES_OTHER_ONLY,
+        "fr" | "pt" => RULES_ZERO_ONE,
+        "ru" | "uk" | "be" => RULES_EAST_SLAVIC,
+        "pl" => RULES_POLISH,
+        "cs" | "sk" => RULES_WEST_SLAVIC,
+        "ro" => RULES_ROMANIAN,
+        "sl" => RULES_SLOVENIAN,
+        "lv" => RULES_LATVIAN,
+        "lt" => RULES_LITHUANIAN,
+        "ga" => RULES_IRISH,
+        "cy" => RULES_WELSH,
+        "he" => RULES_HEBREW,
+        "ar" => RULES_ARABIC,
+        _ => RULES_ONE,
+    }
+}
+
+/// Returns every plural category a translation has to provide for the given language.
+pub fn required_categories(lang: &str) -> Vec<PluralCategory> {
+    plural_rules(lang)
+        .iter()
+        .map(|rule| rule.category)
+        .chain(std::iter::once(PluralCategory::Other))
+        .collect()
+}
+
+/// Pairs every plural rule of a language with the form it selects, followed by the `other` form
+/// to use when none of the rules match. Conditions are written against `count`, the variable
+/// holding the absolute value of the count.
+///
+/// Rules whose category has no form fall back to the `other` form, which keeps them from being
+/// shadowed by the rules that come after them.
+pub fn plural_branches<'a>(
+    lang: &str,
+    forms: &'a [(PluralCategory, String)],
+    count: &str,
+) -> (Vec<(String, &'a str)>, &'a str) {
+    let form_of = |category: PluralCategory| {
+        forms
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, form)| form.as_str())
+    };
+    let other = form_of(PluralCategory::Other).unwrap_or_default();
+    let variable = regex::Regex::new(r"\bn\b").unwrap();
+    let branches = plural_rules(lang)
+        .iter()
+        .map(|rule| {
+            (
+                variable
+                    .replace_all(rule.condition, regex::NoExpand(count))
+                    .to_string(),
+                form_of(rule.category).unwrap_or(other),
+            )
+        })
+        .collect();
+    (branches, other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates a plural rule condition for a count, supporting the operators the rules use.
+    fn evaluate(condition: &str, n: i64) -> bool {
+        let tokens: Vec<String> = condition
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        let mut pos = 0;
+        or(&tokens, &mut pos, n)
+    }
+
+    fn or(tokens: &[String], pos: &mut usize, n: i64) -> bool {
+        let mut value = and(tokens, pos, n);
+        while tokens.get(*pos).is_some_and(|token| token == "||") {
+            *pos += 1;
+            value |= and(tokens, pos, n);
+        }
+        value
+    }
+
+    fn and(tokens: &[String], pos: &mut usize, n: i64) -> bool {
+        let mut value = comparison(tokens, pos, n);
+        while tokens.get(*pos).is_some_and(|token| token == "&&") {
+            *pos += 1;
+            value &= comparison(tokens, pos, n);
+        }
+        value
+    }
+
+    fn comparison(tokens: &[String], pos: &mut usize, n: i64) -> bool {
+        if tokens[*pos] == "(" {
+            *pos += 1;
+            let value = or(tokens, pos, n);
+            *pos += 1;
+            return value;
+        }
+        let left = operand(tokens, pos, n);
+        let operator = tokens[*pos].clone();
+        *pos += 1;
+        let right = operand(tokens, pos, n);
+        match operator.as_str() {
+            "==" => left == right,
+            "!=" => left != right,
+            "<" => left < right,
+            "<=" => left <= right,
+            ">" => left > right,
+            ">=" => left >= right,
+            _ => panic!("unknown operator {}", operator),
+        }
+    }
+
+    fn operand(tokens: &[String], pos: &mut usize, n: i64) -> i64 {
+        let value = |token: &str| match token {
+            "n" => n,
+            _ => token.parse().unwrap(),
+        };
+        let mut result = value(&tokens[*pos]);
+        *pos += 1;
+        if tokens.get(*pos).is_some_and(|token| token == "%") {
+            result %= value(&tokens[*pos + 1]);
+            *pos += 2;
+        }
+        result
+    }
+
+    fn category(lang: &str, n: i64) -> PluralCategory {
+        plural_rules(lang)
+            .iter()
+            .find(|rule| evaluate(rule.condition, n))
+            .map(|rule| rule.category)
+            .unwrap_or(PluralCategory::Other)
+    }
+
+    /// Checks the categories of a language against the CLDR samples of every one of them.
+    fn assert_categories(lang: &str, samples: &[(PluralCategory, &[i64])]) {
+        for (expected, counts) in samples {
+            for n in *counts {
+                assert_eq!(category(lang, *n), *expected, "{} for {}", lang, n);
+            }
+        }
+    }
+
+    use PluralCategory::*;
+
+    #[test]
+    fn english() {
+        assert_categories("en", &[(One, &[1]), (Other, &[0, 2, 5, 11, 101])]);
+    }
+
+    #[test]
+    fn french_and_portuguese() {
+        assert_categories("fr", &[(One, &[0, 1]), (Other, &[2, 10, 100])]);
+        assert_categories("pt-BR", &[(One, &[0, 1]), (Other, &[2, 10])]);
+        assert_categories("pt_PT", &[(One, &[1]), (Other, &[0, 2, 10])]);
+    }
+
+    #[test]
+    fn east_slavic() {
+        for lang in ["ru", "uk", "be"] {
+            assert_categories(
+                lang,
+                &[
+                    (One, &[1, 21, 101, 1001]),
+                    (Few, &[2, 3, 4, 22, 24, 102]),
+                    (Many, &[0, 5, 11, 12, 14, 19, 100, 111, 112]),
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn polish() {
+        assert_categories(
+            "pl",
+            &[
+                (One, &[1]),
+                (Few, &[2, 3, 4, 22, 104]),
+                (Many, &[0, 5, 12, 14, 21, 112]),
+            ],
+        );
+    }
+
+    #[test]
+    fn czech_and_slovak() {
+        for lang in ["cs", "sk"] {
+            assert_categories(
+                lang,
+                &[(One, &[1]), (Few, &[2, 3, 4]), (Other, &[0, 5, 22])],
+            );
+        }
+    }
+
+    #[test]
+    fn romanian() {
+        assert_categories(
+            "ro",
+            &[
+                (One, &[1]),
+                (Few, &[0, 2, 19, 101, 119, 201, 1001]),
+                (Other, &[20, 100, 120, 1000]),
+            ],
+        );
+    }
+
+    #[test]
+    fn slovenian() {
+        assert_categories(
+            "sl",
+            &[
+                (One, &[1, 101, 201]),
+                (Two, &[2, 102]),
+                (Few, &[3, 4, 103, 104]),
+                (Other, &[0, 5, 100, 105]),
+            ],
+        );
+    }
+
+    #[test]
+    fn latvian() {
+        assert_categories(
+            "lv",
+            &[
+                (Zero, &[0, 10, 11, 19, 20, 30, 111]),
+                (One, &[1, 21, 31, 101]),
+                (Other, &[2, 9, 22, 102]),
+            ],
+        );
+    }
+
+    #[test]
+    fn lithuanian() {
+        assert_categories(
+            "lt",
+            &[
+                (One, &[1, 21, 31, 101]),
+                (Few, &[2, 9, 22, 29, 102]),
+                (Other, &[0, 10, 11, 19, 20, 111]),
+            ],
+        );
+    }
+
+    #[test]
+    fn irish() {
+        assert_categories(
+            "ga",
+            &[
+                (One, &[1]),
+                (Two, &[2]),
+                (Few, &[3, 6]),
+                (Many, &[7, 10]),
+                (Other, &[0, 11, 100]),
+            ],
+        );
+    }
+
+    #[test]
+    fn welsh() {
+        assert_categories(
+            "cy",
+            &[
+                (Zero, &[0]),
+                (One, &[1]),
+                (Two, &[2]),
+                (Few, &[3]),
+                (Many, &[6]),
+                (Other, &[4, 5, 7, 100]),
+            ],
+        );
+    }
+
+    #[test]
+    fn hebrew_and_arabic() {
+        assert_categories("he", &[(One, &[1]), (Two, &[2]), (Other, &[0, 3, 20])]);
+        assert_categories(
+            "ar",
+            &[
+                (Zero, &[0]),
+                (One, &[1]),
+                (Two, &[2]),
+                (Few, &[3, 10, 103]),
+                (Many, &[11, 99, 111]),
+                (Other, &[100, 101, 102]),
+            ],
+        );
+    }
+
+    #[test]
+    fn languages_without_plurals() {
+        assert_categories("ja", &[(Other, &[0, 1, 2])]);
+        assert_eq!(required_categories("zh-Hant"), vec![Other]);
+    }
+
+    #[test]
+    fn required_categories_follow_the_rules() {
+        assert_eq!(
+            required_categories("cy"),
+            vec![Zero, One, Two, Few, Many, Other]
+        );
+        assert_eq!(required_categories("lv"), vec![Zero, One, Other]);
+    }
+}
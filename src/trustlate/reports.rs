@@ -0,0 +1,327 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{
+    config::Config,
+    errors::SourceSpan,
+    translations_tree::{locate_key, TranslationsTree, TreeComparisonDifference, TreePath},
+};
+
+/// The formats `check` can report the differences in.
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum CheckFormat {
+    /// A colored summary per language, with a table of differences when they are shown
+    #[default]
+    Table,
+    /// A JSON document listing every difference
+    Json,
+    /// A SARIF 2.1.0 log, for code scanning tools
+    Sarif,
+    /// A JUnit XML report with a test case per translation, for test reporters
+    Junit,
+}
+
+/// How much a kind of difference matters to `check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Makes `check` fail and keeps `generate` from generating code
+    Error,
+    /// Is reported but lets `check` pass, unless it is strict
+    Warning,
+    /// Is not reported
+    Ignore,
+}
+
+impl Severity {
+    /// The severity of a kind of difference when the configuration does not set one.
+    ///
+    /// Differences that break the generated clients are errors, while translations that only
+    /// a target language has, or that are still waiting for a translator, are warnings.
+    pub fn default_for(difference: &TreeComparisonDifference) -> Self {
+        match difference {
+            TreeComparisonDifference::ExtraNode(_) | TreeComparisonDifference::Untranslated(_) => {
+                Self::Warning
+            }
+            _ => Self::Error,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Ignore => "ignore",
+        }
+    }
+}
+
+/// The differences between the translations of a target language and the base language ones,
+/// together with their severity.
+#[derive(Debug)]
+pub struct LanguageCheck<'a> {
+    pub lang: &'a str,
+    pub file: PathBuf,
+    pub differences: Vec<(TreeComparisonDifference, Severity)>,
+}
+
+impl LanguageCheck<'_> {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.differences
+            .iter()
+            .filter(|(_, diff_severity)| *diff_severity == severity)
+            .count()
+    }
+}
+
+pub fn json_report(config: &Config, checks: &[LanguageCheck], ok: bool) -> String {
+    let languages: Vec<Value> = checks
+        .iter()
+        .map(|check| {
+            let differences: Vec<Value> = check
+                .differences
+                .iter()
+                .map(|(diff, severity)| {
+                    json!({
+                        "kind": diff.kind(),
+                        "severity": severity.keyword(),
+                        "path": diff.path().steps(),
+                        "key": diff.path().to_string(),
+                        "message": diff.to_string(),
+                        "details": difference_details(diff),
+                    })
+                })
+                .collect();
+            json!({
+                "lang": check.lang,
+                "file": check.file.display().to_string(),
+                "errors": check.count(Severity::Error),
+                "warnings": check.count(Severity::Warning),
+                "differences": differences,
+            })
+        })
+        .collect();
+
+    let report = json!({
+        "base_lang": config.base_lang,
+        "ok": ok,
+        "languages": languages,
+    });
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+fn difference_details(diff: &TreeComparisonDifference) -> Value {
+    match diff {
+        TreeComparisonDifference::DifferentParameters(_, parameter_differences) => json!({
+            "missing": parameter_differences.missing,
+            "extra": parameter_differences.extra,
+            "renamed": parameter_differences
+                .renamed
+                .iter()
+                .map(|(expected, found)| json!({ "expected": expected, "found": found }))
+                .collect::<Vec<Value>>(),
+        }),
+        TreeComparisonDifference::MissingPluralCategories(_, categories) => json!({
+            "categories": categories
+                .iter()
+                .map(|category| category.keyword())
+                .collect::<Vec<&str>>(),
+        }),
+        TreeComparisonDifference::MissingSelectCases(_, cases) => json!({ "cases": cases }),
+        TreeComparisonDifference::DifferentParameterTypes(_, parameters) => {
+            json!({ "parameters": parameters })
+        }
+        _ => json!({}),
+    }
+}
+
+pub fn sarif_report(checks: &[LanguageCheck]) -> String {
+    let rules: Vec<Value> = [
+        (
+            "missing-node",
+            "The translation is missing in the target language",
+        ),
+        (
+            "extra-node",
+            "The translation does not exist in the base language",
+        ),
+        (
+            "different-node-type",
+            "The translation is not of the same kind as in the base language",
+        ),
+        (
+            "different-parameters",
+            "The placeholders differ from the base language ones",
+        ),
+        (
+            "missing-plural-categories",
+            "The plural forms required by the language are missing",
+        ),
+        (
+            "missing-select-cases",
+            "The select cases of the base language are missing",
+        ),
+        (
+            "different-parameter-types",
+            "The placeholder types differ from the base language ones",
+        ),
+        (
+            "untranslated",
+            "The translation still holds the filling text",
+        ),
+    ]
+    .iter()
+    .map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+    .collect();
+
+    let mut results = vec![];
+    for check in checks {
+        let source = std::fs::read_to_string(&check.file).unwrap_or_default();
+        let uri = check.file.display().to_string();
+        for (diff, severity) in &check.differences {
+            let (line, column) = locate(&source, diff.path());
+            results.push(json!({
+                "ruleId": diff.kind(),
+                "level": severity.keyword(),
+                "message": {
+                    "text": format!("{}: {}: {}", check.lang.to_uppercase(), diff.path(), diff),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri.trim_start_matches("./") },
+                        "region": { "startLine": line, "startColumn": column },
+                    },
+                    "logicalLocations": [{
+                        "fullyQualifiedName": diff.path().steps().join("."),
+                        "kind": "member",
+                    }],
+                }],
+            }));
+        }
+    }
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "trustlate",
+                    "version": "0.2.0",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap()
+}
+
+/// Returns the line and column of the key at `path`, or of its closest ancestor the file has when
+/// the key is missing.
+fn locate(source: &str, path: &TreePath) -> (usize, usize) {
+    let steps = path.steps();
+    (0..=steps.len())
+        .rev()
+        .find_map(|len| locate_key(source, &steps[..len]))
+        .and_then(|offset| SourceSpan::at_offset(source, offset))
+        .map(|span| (span.line, span.column))
+        .unwrap_or((1, 1))
+}
+
+/// Builds a JUnit report with a test suite per target language and a test case per translation
+/// of the base language, plus one per translation that only the target language has.
+pub fn junit_report(base_tree: &TranslationsTree, checks: &[LanguageCheck]) -> String {
+    let leaf_paths = base_tree.leaf_paths();
+    let mut suites = String::new();
+    let (mut total_tests, mut total_failures) = (0, 0);
+
+    for check in checks {
+        let mut cases: Vec<(String, Vec<&(TreeComparisonDifference, Severity)>)> = leaf_paths
+            .iter()
+            .map(|leaf_path| {
+                let diffs = check
+                    .differences
+                    .iter()
+                    .filter(|(diff, _)| leaf_path.starts_with(diff.path()))
+                    .collect();
+                (leaf_path.to_string(), diffs)
+            })
+            .collect();
+        for entry in &check.differences {
+            if let (TreeComparisonDifference::ExtraNode(path), _) = entry {
+                cases.push((path.to_string(), vec![entry]));
+            }
+        }
+
+        let is_failure = |diffs: &[&(TreeComparisonDifference, Severity)]| {
+            diffs
+                .iter()
+                .any(|(_, severity)| *severity == Severity::Error)
+        };
+        let failures = cases.iter().filter(|(_, diffs)| is_failure(diffs)).count();
+        total_tests += cases.len();
+        total_failures += failures;
+
+        suites += &format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" file=\"{}\">\n",
+            escape_xml(check.lang),
+            cases.len(),
+            failures,
+            escape_xml(&check.file.display().to_string())
+        );
+        for (name, diffs) in &cases {
+            let classname = format!("trustlate.{}", check.lang);
+            if diffs.is_empty() {
+                suites += &format!(
+                    "    <testcase classname=\"{}\" name=\"{}\"/>\n",
+                    escape_xml(&classname),
+                    escape_xml(name)
+                );
+                continue;
+            }
+
+            let message = diffs
+                .iter()
+                .map(|(diff, severity)| {
+                    format!("{}: {}: {}", severity.keyword(), diff.path(), diff)
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            let body = match diffs
+                .iter()
+                .find(|(_, severity)| *severity == Severity::Error)
+            {
+                Some((diff, _)) => format!(
+                    "<failure type=\"{}\" message=\"{}\">{}</failure>",
+                    diff.kind(),
+                    escape_xml(&diff.to_string()),
+                    escape_xml(&message)
+                ),
+                // Warnings do not fail the test case, but they are still shown.
+                None => format!("<system-out>{}</system-out>", escape_xml(&message)),
+            };
+            suites += &format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n      {}\n    </testcase>\n",
+                escape_xml(&classname),
+                escape_xml(name),
+                body
+            );
+        }
+        suites += "  </testsuite>\n";
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"trustlate\" tests=\"{total_tests}\" failures=\"{total_failures}\">\n{suites}</testsuites>"
+    )
+}
+
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,285 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::translations_tree::{TranslationTreeNode, TranslationsTree};
+
+/// A member of a JSON object of a translations file, with the byte offsets it spans.
+struct Member {
+    key: String,
+    start: usize,
+    key_end: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+/// How a translations file is written, so that the translations added to it look like the rest.
+struct Style<'a> {
+    indent: &'a str,
+    newline: &'a str,
+    colon: &'a str,
+}
+
+/// Returns the translations file `original` with the translations of `tree`, only changing the
+/// text of the translations that differ and of the objects whose keys changed.
+///
+/// The rest of the file is kept as it is written: the layout of the untouched objects, their line
+/// endings and the escapes of their texts. Returns `None` when `original` is not a JSON object.
+pub fn rewrite_like(original: &str, tree: &TranslationsTree, indent: &str) -> Option<String> {
+    let start = original.len()
+        - original
+            .trim_start_matches(['\u{feff}', ' ', '\t', '\r', '\n'])
+            .len();
+    if !original[start..].starts_with('{') {
+        return None;
+    }
+    let (members, end) = object_members(original, start)?;
+    let style = Style {
+        indent,
+        newline: if original.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        },
+        colon: members
+            .first()
+            .map(|member| &original[member.key_end..member.value_start])
+            .unwrap_or(": "),
+    };
+    let object = rewrite_object(original, start, end, &members, &tree.children, 0, &style)?;
+    Some(format!(
+        "{}{}{}",
+        &original[..start],
+        object,
+        &original[end..]
+    ))
+}
+
+/// Rewrites the object spanning `start..end` of `source`, whose members are `members`, so that it
+/// holds `children`.
+///
+/// Members are written in the order of `children`, keeping the separators of the ones that are
+/// still next to each other.
+fn rewrite_object(
+    source: &str,
+    start: usize,
+    end: usize,
+    members: &[Member],
+    children: &IndexMap<String, Box<TranslationTreeNode>>,
+    depth: usize,
+    style: &Style,
+) -> Option<String> {
+    if children.is_empty() {
+        return Some(match members.is_empty() {
+            true => source[start..end].to_string(),
+            false => "{}".to_string(),
+        });
+    }
+
+    let (leading, trailing) = match (members.first(), members.last()) {
+        (Some(first), Some(last)) => (
+            source[start + 1..first.start].to_string(),
+            source[last.value_end..end - 1].to_string(),
+        ),
+        _ => (
+            format!("{}{}", style.newline, style.indent.repeat(depth + 1)),
+            format!("{}{}", style.newline, style.indent.repeat(depth)),
+        ),
+    };
+    let separator = match members.get(..2) {
+        _ if leading.contains('\n') => format!(",{}", leading),
+        Some([first, second]) => source[first.value_end..second.start].to_string(),
+        _ => ", ".to_string(),
+    };
+
+    let mut object = format!("{{{}", leading);
+    let mut previous: Option<usize> = None;
+    for (idx, (key, node)) in children.iter().enumerate() {
+        let position = members.iter().position(|member| &member.key == key);
+        if idx > 0 {
+            object += &match (previous, position) {
+                (Some(prev), Some(pos)) if prev + 1 == pos => {
+                    source[members[prev].value_end..members[pos].start].to_string()
+                }
+                _ => separator.clone(),
+            };
+        }
+        object += &match position {
+            Some(pos) => {
+                let member = &members[pos];
+                format!(
+                    "{}{}",
+                    &source[member.start..member.value_start],
+                    rewrite_value(source, member, node, depth + 1, style)?
+                )
+            }
+            None => format!(
+                "{}{}{}",
+                serde_json::to_string(key).ok()?,
+                style.colon,
+                serialize_node(node, depth + 1, style)?
+            ),
+        };
+        previous = position;
+    }
+    Some(object + &trailing + "}")
+}
+
+/// Returns the text of the value of `member` once it holds `node`, which is the text it already
+/// has when it did not change.
+fn rewrite_value(
+    source: &str,
+    member: &Member,
+    node: &TranslationTreeNode,
+    depth: usize,
+    style: &Style,
+) -> Option<String> {
+    let text = &source[member.value_start..member.value_end];
+    match node {
+        TranslationTreeNode::Leaf(leaf) => {
+            match serde_json::from_str::<String>(text).ok().as_deref() == Some(leaf.raw()) {
+                true => Some(text.to_string()),
+                false => serialize_node(node, depth, style),
+            }
+        }
+        TranslationTreeNode::NonLeaf(children) if text.starts_with('{') => {
+            let (members, end) = object_members(source, member.value_start)?;
+            rewrite_object(
+                source,
+                member.value_start,
+                end,
+                &members,
+                children,
+                depth,
+                style,
+            )
+        }
+        TranslationTreeNode::NonLeaf(_) => serialize_node(node, depth, style),
+    }
+}
+
+/// Writes a node that is not in the file yet, indented to the given depth.
+fn serialize_node(node: &TranslationTreeNode, depth: usize, style: &Style) -> Option<String> {
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(style.indent.as_bytes());
+    let mut json = vec![];
+    node.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut json, formatter,
+    ))
+    .ok()?;
+    let json = String::from_utf8(json).ok()?;
+    let newline = format!("{}{}", style.newline, style.indent.repeat(depth));
+    Some(json.replace('\n', &newline))
+}
+
+/// Reads the members of the JSON object starting at `start`, returning them along with the offset
+/// the object ends at. Returns `None` for the values that are not texts nor objects, which
+/// translation files do not have.
+fn object_members(source: &str, start: usize) -> Option<(Vec<Member>, usize)> {
+    let mut members = vec![];
+    let mut pos = start + 1;
+    loop {
+        pos = skip_whitespace(source, pos);
+        match source.as_bytes().get(pos)? {
+            b'}' => return Some((members, pos + 1)),
+            b',' if !members.is_empty() => pos += 1,
+            b'"' => {
+                let key_end = string_end(source, pos)?;
+                let colon = skip_whitespace(source, key_end);
+                if source.as_bytes().get(colon) != Some(&b':') {
+                    return None;
+                }
+                let value_start = skip_whitespace(source, colon + 1);
+                let value_end = match source.as_bytes().get(value_start)? {
+                    b'"' => string_end(source, value_start)?,
+                    b'{' => object_members(source, value_start)?.1,
+                    _ => return None,
+                };
+                members.push(Member {
+                    key: serde_json::from_str(&source[pos..key_end]).ok()?,
+                    start: pos,
+                    key_end,
+                    value_start,
+                    value_end,
+                });
+                pos = value_end;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the offset right after the JSON string starting at `start`.
+fn string_end(source: &str, start: usize) -> Option<usize> {
+    let mut bytes = source.as_bytes().iter().enumerate().skip(start + 1);
+    while let Some((idx, byte)) = bytes.next() {
+        match byte {
+            b'\\' => {
+                bytes.next();
+            }
+            b'"' => return Some(idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn skip_whitespace(source: &str, pos: usize) -> usize {
+    pos + source[pos..].len() - source[pos..].trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(original: &str, translations: &str) -> Option<String> {
+        let tree = TranslationsTree::from_json(translations).unwrap();
+        rewrite_like(original, &tree, "  ")
+    }
+
+    #[test]
+    fn untouched_files_are_kept_as_they_are() {
+        let original = "\u{feff}{\r\n\t\"b\" : \"B\\u00e9\",\r\n\t\"a\":{ \"c\": \"C\" }\r\n}\r\n";
+        assert_eq!(
+            rewrite(original, r#"{"b": "Bé", "a": {"c": "C"}}"#).as_deref(),
+            Some(original)
+        );
+    }
+
+    #[test]
+    fn only_the_changed_texts_are_rewritten() {
+        let original =
+            "{\n    \"a\": \"A\\u00e9\",\n    \"b\":   \"B\",\n    \"c\": {\"d\": \"D\"}\n}\n";
+        assert_eq!(
+            rewrite(original, r#"{"a": "Aé", "b": "Bé", "c": {"d": "Dé"}}"#).as_deref(),
+            Some("{\n    \"a\": \"A\\u00e9\",\n    \"b\":   \"Bé\",\n    \"c\": {\"d\": \"Dé\"}\n}\n")
+        );
+    }
+
+    #[test]
+    fn members_follow_the_order_of_the_translations() {
+        let original = "{\n  \"b\": \"B\",\n  \"a\": \"A\",\n  \"old\": \"Old\"\n}";
+        assert_eq!(
+            rewrite(original, r#"{"a": "A", "b": "B"}"#).as_deref(),
+            Some("{\n  \"a\": \"A\",\n  \"b\": \"B\"\n}")
+        );
+    }
+
+    #[test]
+    fn added_members_are_written_like_the_rest() {
+        let original = "{\r\n  \"a\": \"A\"\r\n}";
+        assert_eq!(
+            rewrite(original, r#"{"a": "A", "g": {"x": "X", "y": "Y"}}"#).as_deref(),
+            Some("{\r\n  \"a\": \"A\",\r\n  \"g\": {\r\n    \"x\": \"X\",\r\n    \"y\": \"Y\"\r\n  }\r\n}")
+        );
+        assert_eq!(
+            rewrite("{}", r#"{"a": "A"}"#).as_deref(),
+            Some("{\n  \"a\": \"A\"\n}")
+        );
+    }
+
+    #[test]
+    fn files_that_are_not_translation_objects_are_left_to_the_caller() {
+        assert!(rewrite("[]", r#"{"a": "A"}"#).is_none());
+        assert!(rewrite("{\"a\": 1}", r#"{"a": "A"}"#).is_none());
+        assert!(rewrite("{\"a\": \"A\"", r#"{"a": "A"}"#).is_none());
+    }
+}
@@ -1,39 +1,124 @@
+use indexmap::IndexMap;
 use regex::Regex;
 use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
-use super::errors::{self, TrustlateError};
+use super::{
+    errors::{json_error_reason, InvalidTranslations, SourceSpan},
+    plurals::{self, PluralCategory},
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TranslationsTree {
     #[serde(flatten)]
-    pub children: HashMap<String, Box<TranslationTreeNode>>,
+    /// The top level nodes, in the order they are written in the translation file.
+    pub children: IndexMap<String, Box<TranslationTreeNode>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(untagged)]
 pub enum TranslationTreeNode {
-    NonLeaf(HashMap<String, Box<TranslationTreeNode>>),
+    NonLeaf(IndexMap<String, Box<TranslationTreeNode>>),
     Leaf(LeafType),
 }
 
+/// A `{{name}}` or `{{name:type}}` placeholder of a translation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub kind: ParameterKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    String,
+    Number,
+    Date,
+    Currency,
+}
+
+impl ParameterKind {
+    pub fn from_annotation(annotation: &str) -> Option<Self> {
+        match annotation {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "date" => Some(Self::Date),
+            "currency" => Some(Self::Currency),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ParameterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterKind::String => write!(f, "string"),
+            ParameterKind::Number => write!(f, "number"),
+            ParameterKind::Date => write!(f, "date"),
+            ParameterKind::Currency => write!(f, "currency"),
+        }
+    }
+}
+
+/// Returns the parameter with the given name, if there is one.
+pub fn find_parameter<'a>(parameters: &'a [Parameter], name: &str) -> Option<&'a Parameter> {
+    parameters.iter().find(|param| param.name == name)
+}
+
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum LeafType {
     LiteralLeaf(String),
     ParametrizedLeaf {
-        parameters: Vec<String>,
+        parameters: Vec<Parameter>,
+        raw: String,
+    },
+    /// A `{{count, plural, one{...} other{...}}}` message, where `parameter` holds the count and
+    /// `parameters` the placeholders used inside of the forms.
+    PluralLeaf {
+        parameter: String,
+        parameters: Vec<Parameter>,
+        forms: Vec<(PluralCategory, String)>,
+        raw: String,
+    },
+    /// A `{{gender, select, male{...} female{...} other{...}}}` message, where `parameter` holds
+    /// the selector and `parameters` the placeholders used inside of the cases.
+    SelectLeaf {
+        parameter: String,
+        parameters: Vec<Parameter>,
+        cases: Vec<(String, String)>,
         raw: String,
     },
 }
 
+/// Two leaves are equal when they are the same kind of message. Their parameters are compared
+/// separately, see [`LeafType::parameter_differences`].
 impl PartialEq for LeafType {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::LiteralLeaf(_), Self::LiteralLeaf(_)) => true,
-            (Self::ParametrizedLeaf { parameters: params_a, .. }, Self::ParametrizedLeaf { parameters: params_b, ..} ) => {
-                params_a.iter().all(|param| params_b.contains(param))
-            },
+            (
+                Self::LiteralLeaf(_) | Self::ParametrizedLeaf { .. },
+                Self::LiteralLeaf(_) | Self::ParametrizedLeaf { .. },
+            ) => true,
+            (
+                Self::PluralLeaf {
+                    parameter: count_a, ..
+                },
+                Self::PluralLeaf {
+                    parameter: count_b, ..
+                },
+            ) => count_a == count_b,
+            (
+                Self::SelectLeaf {
+                    parameter: selector_a,
+                    ..
+                },
+                Self::SelectLeaf {
+                    parameter: selector_b,
+                    ..
+                },
+            ) => selector_a == selector_b,
             _ => false,
         }
     }
@@ -47,85 +132,530 @@ impl<'de> Deserialize<'de> for LeafType {
         let value = Value::deserialize(deserializer)?;
 
         match value {
-            Value::String(value) => {
-                let param_re = Regex::new(r"\{\{(.+?)\}\}").unwrap();
-                if param_re.is_match(&value) {
-                    let params = param_re
-                        .captures_iter(&value)
-                        .filter_map(|caps| caps.get(1))
-                        .map(|mat| mat.as_str().to_string())
-                        .collect();
-                    Ok(LeafType::ParametrizedLeaf {
-                        parameters: params,
-                        raw: value,
-                    })
-                } else {
-                    Ok(LeafType::LiteralLeaf(value))
-                }
-            }
+            Value::String(value) => LeafType::parse(value).map_err(serde::de::Error::custom),
             _ => Err(serde::de::Error::custom("Only string values are valid")),
         }
     }
 }
 
-
 impl Serialize for LeafType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         match self {
-            LeafType::LiteralLeaf(val) => {
-                serializer.serialize_str(val)
+            LeafType::LiteralLeaf(val) => serializer.serialize_str(val),
+            LeafType::ParametrizedLeaf { raw, .. }
+            | LeafType::PluralLeaf { raw, .. }
+            | LeafType::SelectLeaf { raw, .. } => serializer.serialize_str(raw),
+        }
+    }
+}
+
+impl LeafType {
+    /// Parses the text of a translation.
+    pub fn parse(value: String) -> Result<LeafType, String> {
+        let branching_re =
+            Regex::new(r"(?s)^\{\{\s*(\w+)\s*,\s*(plural|select)\s*,(.*)\}\}$").unwrap();
+        let param_re = Regex::new(r"\{\{(.+?)\}\}").unwrap();
+        if let Some(caps) = branching_re.captures(value.trim()) {
+            let parameter = caps[1].to_string();
+            let branches = parse_branches(&caps[3])?;
+            let parameters = parse_parameters(
+                branches.iter().map(|(_, branch)| branch.as_str()),
+                Some(&parameter),
+            )?;
+            if &caps[2] == "plural" {
+                let mut forms = Vec::new();
+                for (keyword, form) in branches {
+                    let category = PluralCategory::from_keyword(&keyword)
+                        .ok_or_else(|| format!("Unknown plural category \"{}\"", keyword))?;
+                    forms.push((category, form));
+                }
+                Ok(LeafType::PluralLeaf {
+                    parameter,
+                    parameters,
+                    forms,
+                    raw: value,
+                })
+            } else {
+                Ok(LeafType::SelectLeaf {
+                    parameter,
+                    parameters,
+                    cases: branches,
+                    raw: value,
+                })
             }
-            LeafType::ParametrizedLeaf { raw, .. } => {
-                serializer.serialize_str(raw)
+        } else if param_re.is_match(&value) {
+            let parameters = parse_parameters(std::iter::once(value.as_str()), None)?;
+            Ok(LeafType::ParametrizedLeaf {
+                parameters,
+                raw: value,
+            })
+        } else {
+            Ok(LeafType::LiteralLeaf(value))
+        }
+    }
+
+    /// Returns the text the leaf was parsed from.
+    pub fn raw(&self) -> &str {
+        match self {
+            LeafType::LiteralLeaf(raw)
+            | LeafType::ParametrizedLeaf { raw, .. }
+            | LeafType::PluralLeaf { raw, .. }
+            | LeafType::SelectLeaf { raw, .. } => raw,
+        }
+    }
+
+    fn plural(
+        parameter: String,
+        parameters: Vec<Parameter>,
+        mut forms: Vec<(PluralCategory, String)>,
+    ) -> Self {
+        forms.sort_by_key(|(category, _)| *category);
+        let raw = format!(
+            "{{{{{}, plural, {}}}}}",
+            parameter,
+            forms
+                .iter()
+                .map(|(category, form)| format!("{}{{{}}}", category, form))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+        LeafType::PluralLeaf {
+            parameter,
+            parameters,
+            forms,
+            raw,
+        }
+    }
+
+    fn select(parameter: String, parameters: Vec<Parameter>, cases: Vec<(String, String)>) -> Self {
+        let raw = format!(
+            "{{{{{}, select, {}}}}}",
+            parameter,
+            cases
+                .iter()
+                .map(|(case, text)| format!("{}{{{}}}", case, text))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+        LeafType::SelectLeaf {
+            parameter,
+            parameters,
+            cases,
+            raw,
+        }
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        match self {
+            LeafType::LiteralLeaf(_) => &[],
+            LeafType::ParametrizedLeaf { parameters, .. }
+            | LeafType::PluralLeaf { parameters, .. }
+            | LeafType::SelectLeaf { parameters, .. } => parameters,
+        }
+    }
+
+    /// Whether the leaf can stand for `reference` in generated code, that is, whether both are of
+    /// the same kind and take the same parameters with the same types.
+    pub fn is_compatible_with(&self, reference: &LeafType) -> bool {
+        self == reference
+            && self.parameter_differences(reference).is_empty()
+            && self.mismatched_parameter_types(reference).is_empty()
+    }
+
+    /// Compares the names of the parameters of the leaf against the ones of `reference`.
+    ///
+    /// A missing and an extra parameter are considered a rename when their names are alike, so that
+    /// a placeholder replaced by an unrelated one is never taken for a misspelling.
+    fn parameter_differences(&self, reference: &LeafType) -> ParameterDifferences {
+        let mut missing: Vec<String> = reference
+            .parameters()
+            .iter()
+            .filter(|param| find_parameter(self.parameters(), &param.name).is_none())
+            .map(|param| param.name.clone())
+            .collect();
+        let mut extra: Vec<String> = self
+            .parameters()
+            .iter()
+            .filter(|param| find_parameter(reference.parameters(), &param.name).is_none())
+            .map(|param| param.name.clone())
+            .collect();
+
+        let mut renamed = Vec::new();
+        missing.retain(|missing_name| {
+            let alike = extra
+                .iter()
+                .position(|extra_name| edit_distance(missing_name, extra_name) <= 2);
+            match alike {
+                Some(idx) => {
+                    renamed.push((missing_name.clone(), extra.remove(idx)));
+                    false
+                }
+                None => true,
             }
+        });
+
+        ParameterDifferences {
+            missing,
+            extra,
+            renamed,
         }
     }
-}
 
-impl Display for LeafType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Returns the names of the parameters the leaf shares with `reference` but with another type.
+    pub fn mismatched_parameter_types(&self, reference: &LeafType) -> Vec<String> {
+        reference
+            .parameters()
+            .iter()
+            .filter(|ref_param| {
+                find_parameter(self.parameters(), &ref_param.name)
+                    .is_some_and(|param| param.kind != ref_param.kind)
+            })
+            .map(|ref_param| ref_param.name.clone())
+            .collect()
+    }
+
+    /// Returns the cases of `reference` the leaf lacks, if both of them are selects.
+    fn missing_select_cases(&self, reference: &LeafType) -> Vec<String> {
+        match (self, reference) {
+            (
+                LeafType::SelectLeaf { cases, .. },
+                LeafType::SelectLeaf {
+                    cases: ref_cases, ..
+                },
+            ) => ref_cases
+                .iter()
+                .map(|(case, _)| case)
+                .filter(|case| !cases.iter().any(|(c, _)| c == *case))
+                .cloned()
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the plural categories the leaf lacks for the given language, if it is a plural.
+    fn missing_plural_categories(&self, lang: &str) -> Vec<PluralCategory> {
         match self {
-            LeafType::LiteralLeaf(val) => write!(f, "\"{}\"", val),
-            LeafType::ParametrizedLeaf { parameters, raw } => {
-                let args = parameters
-                    .iter()
-                    .fold("".to_string(), |acc, el| format!("{}{}:string,", acc, el));
-                // remove the final ","
-                let args = args.strip_suffix(",").unwrap();
-                let mut body = raw.clone();
-                for parameter in parameters {
-                    body = body.replace(&format!("{{{{{}}}}}", parameter), &format!("${{{}}}", parameter));
+            LeafType::PluralLeaf { forms, .. } => plurals::required_categories(lang)
+                .into_iter()
+                .filter(|category| !forms.iter().any(|(c, _)| c == category))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Parses the `one{...} other{...}` part of a plural or select message.
+fn parse_branches(body: &str) -> Result<Vec<(String, String)>, String> {
+    let mut branches: Vec<(String, String)> = Vec::new();
+    let mut rest = body.trim_start();
+
+    while !rest.is_empty() {
+        let open = rest
+            .find('{')
+            .ok_or_else(|| format!("Expected a '{{' after the case name in \"{}\"", rest))?;
+        let keyword = rest[..open].trim();
+        if keyword.is_empty() || !keyword.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("Invalid case name \"{}\"", keyword));
+        }
+
+        let mut depth = 0;
+        let mut close = None;
+        for (idx, c) in rest[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                close = Some(open + idx);
+                break;
+            }
+        }
+        let close = close.ok_or_else(|| format!("Unclosed case \"{}\"", keyword))?;
+
+        if branches.iter().any(|(k, _)| k == keyword) {
+            return Err(format!("Repeated case \"{}\"", keyword));
+        }
+        branches.push((keyword.to_string(), rest[open + 1..close].to_string()));
+        rest = rest[close + 1..].trim_start();
+    }
+
+    if !branches.iter().any(|(k, _)| k == "other") {
+        return Err("Plural and select messages must have an \"other\" case".to_string());
+    }
+
+    Ok(branches)
+}
+
+/// Collects the parameters of the placeholders of some texts, leaving out the one named `exclude`.
+///
+/// A parameter can appear more than once, but at most one type can be given to it. Parameters
+/// without a type are strings.
+fn parse_parameters<'a>(
+    texts: impl Iterator<Item = &'a str>,
+    exclude: Option<&str>,
+) -> Result<Vec<Parameter>, String> {
+    let param_re = Regex::new(r"\{\{(.+?)\}\}").unwrap();
+    let mut parameters: Vec<Parameter> = Vec::new();
+    let mut annotated: Vec<String> = Vec::new();
+
+    for text in texts {
+        for caps in param_re.captures_iter(text) {
+            let (name, kind) = match caps[1].split_once(':') {
+                Some((name, annotation)) => {
+                    let kind =
+                        ParameterKind::from_annotation(annotation.trim()).ok_or_else(|| {
+                            format!(
+                                "Unknown type \"{}\" for parameter \"{}\"",
+                                annotation.trim(),
+                                name.trim()
+                            )
+                        })?;
+                    (name.trim().to_string(), Some(kind))
+                }
+                None => (caps[1].trim().to_string(), None),
+            };
+            if Some(name.as_str()) == exclude {
+                continue;
+            }
+
+            match parameters.iter_mut().find(|param| param.name == name) {
+                Some(param) => {
+                    if let Some(kind) = kind {
+                        if annotated.contains(&name) && param.kind != kind {
+                            return Err(format!(
+                                "Parameter \"{}\" is used both as {} and {}",
+                                name, param.kind, kind
+                            ));
+                        }
+                        param.kind = kind;
+                        annotated.push(name);
+                    }
+                }
+                None => {
+                    if kind.is_some() {
+                        annotated.push(name.clone());
+                    }
+                    parameters.push(Parameter {
+                        name,
+                        kind: kind.unwrap_or(ParameterKind::String),
+                    });
                 }
-                write!(f, "({})=>`{}`", args, body)
             }
         }
     }
+
+    Ok(parameters)
+}
+
+/// Returns the names of the `{{...}}` placeholders of a text, in order of appearance.
+pub fn placeholders(text: &str) -> Vec<String> {
+    let param_re = Regex::new(r"\{\{(.+?)\}\}").unwrap();
+    param_re
+        .captures_iter(text)
+        .filter_map(|caps| caps.get(1))
+        .map(|mat| {
+            let placeholder = mat.as_str();
+            placeholder
+                .split_once(':')
+                .map(|(name, _)| name)
+                .unwrap_or(placeholder)
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Returns the number of single character edits needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Renames the `{{...}}` placeholders of a text named `from` to `to`, keeping their types.
+fn rename_placeholders(text: &str, from: &str, to: &str) -> String {
+    let param_re = Regex::new(r"\{\{(.+?)\}\}").unwrap();
+    param_re
+        .replace_all(text, |caps: &regex::Captures| {
+            let (name, annotation) = match caps[1].split_once(':') {
+                Some((name, annotation)) => (name, format!(":{}", annotation)),
+                None => (&caps[1], "".to_string()),
+            };
+            if name.trim() == from {
+                format!("{{{{{}{}}}}}", to, annotation)
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Replaces the text of a translation with `filling_str` while keeping its placeholders, so that
+/// the filled translation stays compatible with the one it was made from.
+fn blank_text(text: &str, filling_str: &str) -> String {
+    let param_re = Regex::new(r"\{\{.+?\}\}").unwrap();
+    param_re
+        .find_iter(text)
+        .fold(filling_str.to_string(), |acc, mat| {
+            format!("{} {}", acc, mat.as_str())
+        })
 }
 
 impl TranslationTreeNode {
-    fn blank_values(&mut self, blank_val: String) {
+    fn blank_values(&mut self, blank_val: String, lang: &str) {
         match self {
+            TranslationTreeNode::Leaf(LeafType::PluralLeaf {
+                parameter,
+                parameters,
+                forms,
+                ..
+            }) => {
+                // The forms are rebuilt from the categories of the language being filled, which
+                // may not be the same ones the reference language has.
+                let other_form = forms
+                    .iter()
+                    .find(|(category, _)| *category == PluralCategory::Other)
+                    .map(|(_, form)| form.as_str())
+                    .unwrap_or_default();
+                let blank_forms = plurals::required_categories(lang)
+                    .into_iter()
+                    .map(|category| (category, blank_text(other_form, &blank_val)))
+                    .collect();
+                *self = TranslationTreeNode::Leaf(LeafType::plural(
+                    parameter.clone(),
+                    parameters.clone(),
+                    blank_forms,
+                ));
+            }
+            TranslationTreeNode::Leaf(LeafType::SelectLeaf {
+                parameter,
+                parameters,
+                cases,
+                ..
+            }) => {
+                let blank_cases = cases
+                    .iter()
+                    .map(|(case, text)| (case.clone(), blank_text(text, &blank_val)))
+                    .collect();
+                *self = TranslationTreeNode::Leaf(LeafType::select(
+                    parameter.clone(),
+                    parameters.clone(),
+                    blank_cases,
+                ));
+            }
+            TranslationTreeNode::Leaf(LeafType::ParametrizedLeaf { raw, .. }) => {
+                *raw = blank_text(raw, &blank_val)
+            }
             TranslationTreeNode::Leaf(val) => *val = LeafType::LiteralLeaf(blank_val),
             TranslationTreeNode::NonLeaf(children) => {
-                for (_, v) in children {
-                    v.blank_values(blank_val.clone());
+                for v in children.values_mut() {
+                    v.blank_values(blank_val.clone(), lang);
+                }
+            }
+        }
+    }
+
+    fn order_as(&mut self, reference: &TranslationTreeNode) {
+        match (self, reference) {
+            (
+                TranslationTreeNode::NonLeaf(children),
+                TranslationTreeNode::NonLeaf(ref_children),
+            ) => {
+                sort_as(children, ref_children);
+                for (key, node) in children.iter_mut() {
+                    if let Some(ref_node) = ref_children.get(key) {
+                        node.order_as(ref_node);
+                    }
                 }
             }
+            (
+                TranslationTreeNode::Leaf(
+                    LeafType::ParametrizedLeaf { parameters, .. }
+                    | LeafType::PluralLeaf { parameters, .. }
+                    | LeafType::SelectLeaf { parameters, .. },
+                ),
+                TranslationTreeNode::Leaf(ref_leaf),
+            ) => {
+                let ref_parameters = ref_leaf.parameters();
+                parameters.sort_by_key(|param| {
+                    ref_parameters
+                        .iter()
+                        .position(|ref_param| ref_param.name == param.name)
+                        .unwrap_or(ref_parameters.len())
+                });
+            }
+            _ => {}
         }
     }
 }
 
+/// Sorts `children` as `ref_children`, leaving out the ones `ref_children` does not have.
+fn sort_as(
+    children: &mut IndexMap<String, Box<TranslationTreeNode>>,
+    ref_children: &IndexMap<String, Box<TranslationTreeNode>>,
+) {
+    children.retain(|key, _| ref_children.contains_key(key));
+    children
+        .sort_by_cached_key(|key, _| ref_children.get_index_of(key).unwrap_or(ref_children.len()));
+}
+
 impl TranslationsTree {
-    pub fn from_file(f: &std::fs::File) -> Result<TranslationsTree, errors::TrustlateError> {
-        let tree = serde_json::from_reader(f)
-            .map_err(|_| TrustlateError::ParseTraslationFileInvalidJson)?;
-        Ok(tree)
+    /// Parses the contents of a translations file.
+    ///
+    /// Errors point to the offending line of `source` and, when the JSON is well formed, every
+    /// invalid translation is reported together with its key.
+    pub fn from_json(source: &str) -> Result<TranslationsTree, Vec<InvalidTranslations>> {
+        let error = match serde_json::from_str(source) {
+            Ok(tree) => return Ok(tree),
+            Err(error) => error,
+        };
+
+        // Syntax errors come with an accurate location, but the untagged tree nodes hide which
+        // values are not valid translations, so they have to be looked for.
+        let mut invalid_values = vec![];
+        if let Ok(Value::Object(children)) = serde_json::from_str::<Value>(source) {
+            find_invalid_values(&children, &TreePath::new(), &mut invalid_values);
+        }
+        if invalid_values.is_empty() {
+            return Err(vec![InvalidTranslations {
+                reason: json_error_reason(&error),
+                tree_path: None,
+                span: SourceSpan::from_json_error(source, &error),
+            }]);
+        }
+
+        let mut errors: Vec<InvalidTranslations> = invalid_values
+            .into_iter()
+            .map(|(tree_path, reason)| InvalidTranslations {
+                reason,
+                span: locate_key(source, tree_path.steps())
+                    .and_then(|offset| SourceSpan::at_offset(source, offset)),
+                tree_path: Some(tree_path),
+            })
+            .collect();
+        // Objects are not read in document order, but errors are reported in it.
+        errors.sort_by_key(|error| error.span.as_ref().map(|span| (span.line, span.column)));
+        Err(errors)
     }
 
-    pub fn compare(&self, other: &TranslationsTree) -> Vec<TreeComparisonDifference> {
+    /// Compares `other`, the translations for `other_lang`, against this tree.
+    pub fn compare(
+        &self,
+        other: &TranslationsTree,
+        other_lang: &str,
+    ) -> Vec<TreeComparisonDifference> {
         let mut differences: Vec<TreeComparisonDifference> = Vec::new();
         let path = TreePath::new();
 
@@ -139,10 +669,16 @@ impl TranslationsTree {
                     self.children.get(or_key).unwrap(),
                     other.children.get(or_key).unwrap(),
                     &path,
+                    other_lang,
                     &mut differences,
                 );
             }
         }
+        for ot_key in other.children.keys() {
+            if !self.children.contains_key(ot_key) {
+                differences.push(TreeComparisonDifference::ExtraNode(path.walk(ot_key)));
+            }
+        }
 
         differences
     }
@@ -152,6 +688,7 @@ impl TranslationsTree {
         original: &TranslationTreeNode,
         other: &TranslationTreeNode,
         path: &TreePath,
+        other_lang: &str,
         differences: &mut Vec<TreeComparisonDifference>,
     ) {
         match (original, other) {
@@ -169,10 +706,16 @@ impl TranslationsTree {
                             or_children.get(or_key).unwrap(),
                             ot_children.get(or_key).unwrap(),
                             &path,
+                            other_lang,
                             differences,
                         );
                     }
                 }
+                for ot_key in ot_children.keys() {
+                    if !or_children.contains_key(ot_key) {
+                        differences.push(TreeComparisonDifference::ExtraNode(path.walk(ot_key)));
+                    }
+                }
             }
             (TranslationTreeNode::NonLeaf(_), TranslationTreeNode::Leaf(_)) => {
                 differences.push(TreeComparisonDifference::DifferentNodeType(path.clone()))
@@ -183,9 +726,37 @@ impl TranslationsTree {
             (TranslationTreeNode::Leaf(type_a), TranslationTreeNode::Leaf(type_b)) => {
                 if *type_a != *type_b {
                     differences.push(TreeComparisonDifference::DifferentNodeType(path.clone()))
+                } else {
+                    let parameter_differences = type_b.parameter_differences(type_a);
+                    if !parameter_differences.is_empty() {
+                        differences.push(TreeComparisonDifference::DifferentParameters(
+                            path.clone(),
+                            parameter_differences,
+                        ))
+                    }
+                    let missing = type_b.missing_plural_categories(other_lang);
+                    if !missing.is_empty() {
+                        differences.push(TreeComparisonDifference::MissingPluralCategories(
+                            path.clone(),
+                            missing,
+                        ))
+                    }
+                    let mismatched = type_b.mismatched_parameter_types(type_a);
+                    if !mismatched.is_empty() {
+                        differences.push(TreeComparisonDifference::DifferentParameterTypes(
+                            path.clone(),
+                            mismatched,
+                        ))
+                    }
+                    let missing = type_b.missing_select_cases(type_a);
+                    if !missing.is_empty() {
+                        differences.push(TreeComparisonDifference::MissingSelectCases(
+                            path.clone(),
+                            missing,
+                        ))
+                    }
                 }
             }
-            // _ => {}
         }
     }
 
@@ -194,35 +765,217 @@ impl TranslationsTree {
         reference: &TranslationsTree,
         differences: &Vec<TreeComparisonDifference>,
         filling_str: &str,
+        lang: &str,
+        prune: bool,
     ) {
         for diff in differences {
             match diff {
-                TreeComparisonDifference::DifferentNodeType(path) => {
+                TreeComparisonDifference::ExtraNode(path) => {
+                    if prune {
+                        self.remove_node_at(path);
+                    }
+                }
+                // Only a translator can fix it.
+                TreeComparisonDifference::Untranslated(_) => {}
+                TreeComparisonDifference::DifferentNodeType(path)
+                | TreeComparisonDifference::DifferentParameterTypes(path, _) => {
                     let ref_node = reference.get_node_at(path);
-                    match &**ref_node {
+                    match ref_node {
                         TranslationTreeNode::Leaf(_) => {
-                            let mut new_node = *ref_node.clone();
-                            new_node.blank_values(filling_str.to_string());
+                            let mut new_node = ref_node.clone();
+                            new_node.blank_values(filling_str.to_string(), lang);
                             self.replace_node_at(new_node, path);
                         }
                         TranslationTreeNode::NonLeaf { .. } => {
-                            let mut new_node = *ref_node.clone();
-                            new_node.blank_values(filling_str.to_string());
+                            let mut new_node = ref_node.clone();
+                            new_node.blank_values(filling_str.to_string(), lang);
                             self.replace_node_at(new_node, path);
                         }
                     }
                 }
-                TreeComparisonDifference::MissingNode(path) | TreeComparisonDifference::DifferentParameters(path) => {
+                TreeComparisonDifference::DifferentParameters(path, parameter_differences) => {
+                    if parameter_differences.missing.is_empty()
+                        && parameter_differences.extra.is_empty()
+                    {
+                        // The placeholders were only misspelled, so the translation is kept.
+                        if let TranslationTreeNode::Leaf(leaf) = self.get_node_at_mut(path) {
+                            let raw = parameter_differences
+                                .renamed
+                                .iter()
+                                .fold(leaf.raw().to_string(), |raw, (expected, found)| {
+                                    rename_placeholders(&raw, found, expected)
+                                });
+                            if let Ok(renamed) = LeafType::parse(raw) {
+                                *leaf = renamed;
+                            }
+                        }
+                    } else {
+                        let mut new_node = reference.get_node_at(path).clone();
+                        new_node.blank_values(filling_str.to_string(), lang);
+                        self.replace_node_at(new_node, path);
+                    }
+                }
+                TreeComparisonDifference::MissingNode(path) => {
                     let ref_node = reference.get_node_at(path);
-                    let mut new_node = ref_node.clone();
-                    new_node.blank_values(filling_str.to_string());
-                    self.insert_node_at(new_node, path);
+                    let mut new_node = Box::new(ref_node.clone());
+                    new_node.blank_values(filling_str.to_string(), lang);
+                    self.insert_node_at(new_node, path, reference);
+                }
+                TreeComparisonDifference::MissingPluralCategories(path, categories) => {
+                    if let TranslationTreeNode::Leaf(LeafType::PluralLeaf {
+                        parameter,
+                        parameters,
+                        forms,
+                        ..
+                    }) = self.get_node_at_mut(path)
+                    {
+                        let other_form = forms
+                            .iter()
+                            .find(|(category, _)| *category == PluralCategory::Other)
+                            .map(|(_, form)| form.clone())
+                            .unwrap_or_default();
+                        let mut forms = forms.clone();
+                        for category in categories {
+                            if !forms.iter().any(|(c, _)| c == category) {
+                                forms.push((*category, blank_text(&other_form, filling_str)));
+                            }
+                        }
+                        let new_node = TranslationTreeNode::Leaf(LeafType::plural(
+                            parameter.clone(),
+                            parameters.clone(),
+                            forms,
+                        ));
+                        self.replace_node_at(new_node, path);
+                    }
+                }
+                TreeComparisonDifference::MissingSelectCases(path, missing_cases) => {
+                    let ref_cases = match reference.get_node_at(path) {
+                        TranslationTreeNode::Leaf(LeafType::SelectLeaf { cases, .. }) => {
+                            cases.clone()
+                        }
+                        _ => continue,
+                    };
+                    if let TranslationTreeNode::Leaf(LeafType::SelectLeaf {
+                        parameter,
+                        parameters,
+                        cases,
+                        ..
+                    }) = self.get_node_at_mut(path)
+                    {
+                        let mut cases = cases.clone();
+                        for (case, text) in &ref_cases {
+                            if missing_cases.contains(case) && !cases.iter().any(|(c, _)| c == case)
+                            {
+                                // Keep the catch-all case last, as it is written in the reference.
+                                let position = cases
+                                    .iter()
+                                    .position(|(c, _)| c == "other")
+                                    .unwrap_or(cases.len());
+                                cases.insert(
+                                    position,
+                                    (case.clone(), blank_text(text, filling_str)),
+                                );
+                            }
+                        }
+                        let new_node = TranslationTreeNode::Leaf(LeafType::select(
+                            parameter.clone(),
+                            parameters.clone(),
+                            cases,
+                        ));
+                        self.replace_node_at(new_node, path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the tree where the nodes and the parameters of every translation are sorted
+    /// as they are in `reference`, so that the generated code of every language shares the same
+    /// layout and signatures. Nodes that `reference` does not have are left out, so that they do
+    /// not make it into the generated code.
+    pub fn ordered_as(&self, reference: &TranslationsTree) -> TranslationsTree {
+        let mut tree = self.clone();
+        sort_as(&mut tree.children, &reference.children);
+        for (key, node) in tree.children.iter_mut() {
+            if let Some(ref_node) = reference.children.get(key) {
+                node.order_as(ref_node);
+            }
+        }
+        tree
+    }
+
+    /// Returns a difference for every translation that still contains `filling`, the text `fix`
+    /// fills the missing translations with.
+    pub fn untranslated(&self, filling: &str) -> Vec<TreeComparisonDifference> {
+        self.leaf_paths()
+            .into_iter()
+            .filter(|path| match self.get_node_at(path) {
+                TranslationTreeNode::Leaf(leaf) => leaf.raw().contains(filling),
+                TranslationTreeNode::NonLeaf(_) => false,
+            })
+            .map(TreeComparisonDifference::Untranslated)
+            .collect()
+    }
+
+    /// Returns the path of every translation of the tree, in document order.
+    pub fn leaf_paths(&self) -> Vec<TreePath> {
+        fn collect(node: &TranslationTreeNode, path: TreePath, acc: &mut Vec<TreePath>) {
+            match node {
+                TranslationTreeNode::Leaf(_) => acc.push(path),
+                TranslationTreeNode::NonLeaf(children) => {
+                    for (key, child) in children {
+                        collect(child, path.walk(key), acc);
+                    }
                 }
             }
         }
+
+        let mut paths = vec![];
+        for (key, node) in &self.children {
+            collect(node, TreePath::new().walk(key), &mut paths);
+        }
+        paths
+    }
+
+    /// Sets the translation at `path`, which `reference` must have, replacing the node the tree has
+    /// there. The groups leading to it are created if missing, in the position they have in
+    /// `reference`, so that the tree keeps its order.
+    pub fn set_translation(
+        &mut self,
+        path: &TreePath,
+        leaf: LeafType,
+        reference: &TranslationsTree,
+    ) {
+        for depth in 1..path.0.len() {
+            let group = TreePath(path.0[..depth].to_vec());
+            match self.find_node_at(&group) {
+                Some(TranslationTreeNode::NonLeaf(_)) => continue,
+                Some(TranslationTreeNode::Leaf(_)) => self.remove_node_at(&group),
+                None => {}
+            }
+            let empty_group = TranslationTreeNode::NonLeaf(IndexMap::new());
+            self.insert_node_at(Box::new(empty_group), &group, reference);
+        }
+
+        match self.find_node_at(path) {
+            Some(_) => self.replace_node_at(TranslationTreeNode::Leaf(leaf), path),
+            None => self.insert_node_at(Box::new(TranslationTreeNode::Leaf(leaf)), path, reference),
+        }
+    }
+
+    /// Returns the node at `path`, if the tree has one there.
+    pub fn find_node_at(&self, path: &TreePath) -> Option<&TranslationTreeNode> {
+        let mut n = self.children.get(path.0.first()?)?;
+        for link in path.0.iter().skip(1) {
+            match &**n {
+                TranslationTreeNode::NonLeaf(children) => n = children.get(link)?,
+                TranslationTreeNode::Leaf(_) => return None,
+            }
+        }
+        Some(n)
     }
 
-    fn get_node_at(&self, path: &TreePath) -> &Box<TranslationTreeNode> {
+    fn get_node_at(&self, path: &TreePath) -> &TranslationTreeNode {
         let mut n = self
             .children
             .get(path.0.first().expect("a non empty path"))
@@ -254,21 +1007,50 @@ impl TranslationsTree {
         n
     }
 
-    fn insert_node_at(&mut self, node: Box<TranslationTreeNode>, path: &TreePath) {
+    /// Inserts a node that `reference` has at `path`, right after the siblings that precede it in
+    /// `reference`, so that the tree keeps the order of the reference.
+    fn insert_node_at(
+        &mut self,
+        node: Box<TranslationTreeNode>,
+        path: &TreePath,
+        reference: &TranslationsTree,
+    ) {
         let mut p = path.clone();
         p.0 = path.0[..path.0.len() - 1].to_vec();
-        let n = if p.0.len() == 0 {
-            self.children.insert(path.0.first().unwrap().clone(), node);
-            return;
+        let key = path.0.last().unwrap().clone();
+        let (children, ref_children) = if p.0.is_empty() {
+            (&mut self.children, &reference.children)
         } else {
-            self.get_node_at_mut(&p)
+            match (self.get_node_at_mut(&p), reference.get_node_at(&p)) {
+                (
+                    TranslationTreeNode::NonLeaf(ref mut children),
+                    TranslationTreeNode::NonLeaf(ref_children),
+                ) => (children, ref_children),
+                _ => return,
+            }
         };
 
-        match *n {
-            TranslationTreeNode::NonLeaf(ref mut children) => {
-                children.insert(path.0.last().unwrap().clone(), node);
-            }
-            TranslationTreeNode::Leaf(_) => {}
+        let position = ref_children
+            .keys()
+            .take_while(|ref_key| **ref_key != key)
+            .filter_map(|ref_key| children.get_index_of(ref_key))
+            .max()
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        children.shift_insert(position, key, node);
+    }
+
+    fn remove_node_at(&mut self, path: &TreePath) {
+        let mut p = path.clone();
+        p.0 = path.0[..path.0.len() - 1].to_vec();
+        let key = path.0.last().unwrap();
+        if p.0.is_empty() {
+            self.children.shift_remove(key);
+            return;
+        }
+
+        if let TranslationTreeNode::NonLeaf(ref mut children) = self.get_node_at_mut(&p) {
+            children.shift_remove(key);
         }
     }
 
@@ -278,6 +1060,46 @@ impl TranslationsTree {
     }
 }
 
+/// Collects the path of every value of a translations file that is not a valid translation,
+/// together with the reason it is not valid.
+fn find_invalid_values(
+    children: &serde_json::Map<String, Value>,
+    path: &TreePath,
+    acc: &mut Vec<(TreePath, String)>,
+) {
+    for (key, child) in children {
+        let path = path.walk(key);
+        match child {
+            Value::Object(grandchildren) => find_invalid_values(grandchildren, &path, acc),
+            Value::String(text) => {
+                if let Err(reason) = LeafType::parse(text.to_string()) {
+                    acc.push((path, reason));
+                }
+            }
+            _ => acc.push((path, "Only string values are valid".to_string())),
+        }
+    }
+}
+
+/// Returns the byte offset of the key reached by following `keys` in the JSON `source`.
+///
+/// Keys are searched one after the other, so the offset is right as long as the keys of the path
+/// are not repeated in the values that come before them.
+pub fn locate_key(source: &str, keys: &[String]) -> Option<usize> {
+    keys.iter().try_fold(0, |from, key| {
+        let quoted = serde_json::to_string(key).ok()?;
+        let mut search_from = from;
+        loop {
+            let found = search_from + source.get(search_from..)?.find(&quoted)?;
+            let after = found + quoted.len();
+            if source[after..].trim_start().starts_with(':') {
+                return Some(found);
+            }
+            search_from = after;
+        }
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct TreePath(Vec<String>);
 
@@ -286,20 +1108,30 @@ impl TreePath {
         Self(vec![])
     }
 
-    pub fn walk(&self, next: &String) -> Self {
+    pub fn walk(&self, next: &str) -> Self {
         let mut current = self.0.clone();
-        current.push(next.clone());
+        current.push(next.to_string());
         Self(current)
     }
+
+    /// The keys to follow from the root of the tree, in order.
+    pub fn steps(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Whether this path is `other` or goes through it.
+    pub fn starts_with(&self, other: &TreePath) -> bool {
+        self.0.starts_with(&other.0)
+    }
 }
 
 impl Display for TreePath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (step_idx, step) in self.0.iter().enumerate() {
             if step_idx == self.0.len() - 1 {
-                write!(f, "{}", step);
+                write!(f, "{}", step)?;
             } else {
-                write!(f, "{} -> ", step);
+                write!(f, "{} -> ", step)?;
             }
         }
         Ok(())
@@ -309,6 +1141,166 @@ impl Display for TreePath {
 #[derive(Debug)]
 pub enum TreeComparisonDifference {
     MissingNode(TreePath),
+    /// A node of a target language that the base language does not have.
+    ExtraNode(TreePath),
     DifferentNodeType(TreePath),
-    DifferentParameters(TreePath),
+    DifferentParameters(TreePath, ParameterDifferences),
+    MissingPluralCategories(TreePath, Vec<PluralCategory>),
+    MissingSelectCases(TreePath, Vec<String>),
+    DifferentParameterTypes(TreePath, Vec<String>),
+    /// A translation that still holds the filling text `fix` left in it.
+    Untranslated(TreePath),
+}
+
+impl TreeComparisonDifference {
+    pub fn path(&self) -> &TreePath {
+        match self {
+            Self::MissingNode(path)
+            | Self::ExtraNode(path)
+            | Self::DifferentNodeType(path)
+            | Self::DifferentParameters(path, _)
+            | Self::MissingPluralCategories(path, _)
+            | Self::MissingSelectCases(path, _)
+            | Self::DifferentParameterTypes(path, _)
+            | Self::Untranslated(path) => path,
+        }
+    }
+
+    /// Every kind of difference, as returned by [`TreeComparisonDifference::kind`].
+    pub const KINDS: &'static [&'static str] = &[
+        "missing-node",
+        "extra-node",
+        "different-node-type",
+        "different-parameters",
+        "missing-plural-categories",
+        "missing-select-cases",
+        "different-parameter-types",
+        "untranslated",
+    ];
+
+    /// A stable identifier of the kind of difference, meant for machine readable reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingNode(_) => "missing-node",
+            Self::ExtraNode(_) => "extra-node",
+            Self::DifferentNodeType(_) => "different-node-type",
+            Self::DifferentParameters(_, _) => "different-parameters",
+            Self::MissingPluralCategories(_, _) => "missing-plural-categories",
+            Self::MissingSelectCases(_, _) => "missing-select-cases",
+            Self::DifferentParameterTypes(_, _) => "different-parameter-types",
+            Self::Untranslated(_) => "untranslated",
+        }
+    }
+}
+
+impl Display for TreeComparisonDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingNode(_) => write!(f, "Missing values"),
+            Self::ExtraNode(_) => write!(f, "Not in the base language"),
+            Self::DifferentNodeType(_) => write!(f, "Different value"),
+            Self::DifferentParameters(_, parameter_differences) => {
+                write!(f, "Incompatible parameters: {}", parameter_differences)
+            }
+            Self::MissingPluralCategories(_, categories) => write!(
+                f,
+                "Missing plural forms: {}",
+                categories
+                    .iter()
+                    .map(|category| category.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Self::MissingSelectCases(_, cases) => {
+                write!(f, "Missing select cases: {}", cases.join(", "))
+            }
+            Self::DifferentParameterTypes(_, parameters) => {
+                write!(f, "Different parameter types: {}", parameters.join(", "))
+            }
+            Self::Untranslated(_) => write!(f, "Not translated yet"),
+        }
+    }
+}
+
+/// The placeholders a translation lacks, has in excess, or has named differently (as
+/// `(expected, found)` pairs) with respect to the base language.
+#[derive(Debug)]
+pub struct ParameterDifferences {
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+}
+
+impl ParameterDifferences {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.renamed.is_empty()
+    }
+}
+
+impl Display for ParameterDifferences {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if !self.missing.is_empty() {
+            parts.push(format!("missing {}", self.missing.join(", ")));
+        }
+        if !self.extra.is_empty() {
+            parts.push(format!("extra {}", self.extra.join(", ")));
+        }
+        if !self.renamed.is_empty() {
+            parts.push(format!(
+                "renamed {}",
+                self.renamed
+                    .iter()
+                    .map(|(expected, found)| format!("{} -> {}", expected, found))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(json: &str) -> TranslationsTree {
+        TranslationsTree::from_json(json).unwrap()
+    }
+
+    fn fixed(base: &str, translations: &str) -> String {
+        let (base, mut translations) = (tree(base), tree(translations));
+        let differences = base.compare(&translations, "fr");
+        translations.harmonize(&base, &differences, "TODO", "fr", false);
+        serde_json::to_string(&translations).unwrap()
+    }
+
+    #[test]
+    fn misspelled_placeholders_are_renamed() {
+        assert_eq!(
+            fixed(r#"{"a": "Hi {{name}}"}"#, r#"{"a": "Salut {{nmae}}"}"#),
+            r#"{"a":"Salut {{name}}"}"#
+        );
+    }
+
+    #[test]
+    fn unrelated_placeholders_are_not_taken_for_a_rename() {
+        let (base, translations) = (
+            tree(r#"{"a": "Hi {{name}}"}"#),
+            tree(r#"{"a": "Salut {{count}}"}"#),
+        );
+        let differences = base.compare(&translations, "fr");
+        match differences.as_slice() {
+            [TreeComparisonDifference::DifferentParameters(_, parameters)] => {
+                assert_eq!(parameters.missing, ["name"]);
+                assert_eq!(parameters.extra, ["count"]);
+                assert!(parameters.renamed.is_empty());
+            }
+            _ => panic!("unexpected differences {:?}", differences),
+        }
+        assert_eq!(
+            fixed(r#"{"a": "Hi {{name}}"}"#, r#"{"a": "Salut {{count}}"}"#),
+            r#"{"a":"TODO {{name}}"}"#
+        );
+    }
 }